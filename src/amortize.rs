@@ -0,0 +1,118 @@
+use crate::finance;
+
+/// One period of a loan repayment schedule.
+#[derive(Debug, PartialEq)]
+pub struct Row {
+    pub period: u32,
+    pub payment: f64,
+    pub interest: f64,
+    pub principal: f64,
+    pub balance: f64,
+}
+
+/// Monthly repayment schedule for a loan at a yearly `rate` (a fraction).
+pub fn schedule(principal: f64, rate: f64, years: f64) -> Result<Vec<Row>, String> {
+    if principal <= 0.0 {
+        return Err("The principal must be positive".to_string());
+    }
+    if !(0.0..=1.0).contains(&rate) {
+        return Err(format!(
+            "Rate {} is out of range, write it as a percentage such as 5%",
+            rate
+        ));
+    }
+    let periods = years * 12.0;
+    if periods < 1.0 || periods.fract() != 0.0 || periods > 1200.0 {
+        return Err(format!("Cannot amortize over {} years", years));
+    }
+
+    let monthly = rate / 12.0;
+    let payment = -finance::pmt(monthly, periods, principal, 0.0, 0.0)?;
+    let mut balance = principal;
+    let mut rows = Vec::new();
+    for period in 1..=periods as u32 {
+        let interest = balance * monthly;
+        let principal_part = if period == periods as u32 {
+            balance
+        } else {
+            payment - interest
+        };
+        balance -= principal_part;
+        rows.push(Row {
+            period,
+            payment: interest + principal_part,
+            interest,
+            principal: principal_part,
+            balance: balance.max(0.0),
+        });
+    }
+    Ok(rows)
+}
+
+/// Renders the schedule as an aligned text table.
+pub fn format_table(rows: &[Row]) -> String {
+    let mut out = format!(
+        "{:>6} {:>12} {:>12} {:>12} {:>14}",
+        "Period", "Payment", "Interest", "Principal", "Balance"
+    );
+    for row in rows {
+        out.push_str(&format!(
+            "\n{:>6} {:>12.2} {:>12.2} {:>12.2} {:>14.2}",
+            row.period, row.payment, row.interest, row.principal, row.balance
+        ));
+    }
+    out
+}
+
+/// The schedule as CSV records, header first.
+pub fn csv_records(rows: &[Row]) -> Vec<Vec<String>> {
+    let mut records = vec![
+        ["period", "payment", "interest", "principal", "balance"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect(),
+    ];
+    for row in rows {
+        records.push(vec![
+            row.period.to_string(),
+            format!("{:.2}", row.payment),
+            format!("{:.2}", row.interest),
+            format!("{:.2}", row.principal),
+            format!("{:.2}", row.balance),
+        ]);
+    }
+    records
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_schedule_pays_off_loan() {
+        let rows = schedule(10_000.0, 0.06, 1.0).unwrap();
+        assert_eq!(rows.len(), 12);
+        assert!((rows[0].payment - 860.66).abs() < 0.01);
+        assert!((rows[0].interest - 50.0).abs() < 1e-9);
+        assert_eq!(rows[11].balance, 0.0);
+        let repaid: f64 = rows.iter().map(|r| r.principal).sum();
+        assert!((repaid - 10_000.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_schedule_errors() {
+        assert!(schedule(10_000.0, 6.0, 1.0).is_err());
+        assert!(schedule(0.0, 0.06, 1.0).is_err());
+        assert!(schedule(10_000.0, 0.06, 0.01).is_err());
+    }
+
+    #[test]
+    fn test_table_and_csv() {
+        let rows = schedule(1_200.0, 0.0, 1.0).unwrap();
+        let table = format_table(&rows);
+        assert!(table.lines().nth(1).unwrap().ends_with("1100.00"));
+        let records = csv_records(&rows);
+        assert_eq!(records.len(), 13);
+        assert_eq!(records[1], vec!["1", "100.00", "0.00", "100.00", "1100.00"]);
+    }
+}
@@ -0,0 +1,100 @@
+//! Degrees-minutes-seconds angles: `45°30'15"`, or `45d30m15s` for
+//! keyboards without a degree sign.
+
+const MARKS: [[char; 2]; 3] = [['°', 'd'], ['\'', 'm'], ['"', 's']];
+
+/// Length of the DMS literal at the start of `chars` (an optional minus,
+/// then degrees with at least one mark), if there is one.
+pub fn scan_dms(chars: &[char]) -> Option<usize> {
+    let mut i = usize::from(chars.first() == Some(&'-'));
+    let mut style = None;
+    for (part, marks) in MARKS.iter().enumerate() {
+        let end = i + chars[i..]
+            .iter()
+            .take_while(|c| c.is_ascii_digit() || **c == '.')
+            .count();
+        let Some(&mark) = chars.get(end) else {
+            break;
+        };
+        // Stick to one style, so `45°30m` is not an angle.
+        let Some(which) = marks.iter().position(|&m| m == mark) else {
+            break;
+        };
+        if end == i || style.is_some_and(|s| s != which) {
+            break;
+        }
+        style = Some(which);
+        i = end + 1;
+        // `45d` alone could be a name; the degree sign is enough.
+        if part == 0 && which == 1 && !chars.get(i).is_some_and(|c| c.is_ascii_digit()) {
+            return None;
+        }
+    }
+    // A letter straight after `45d30m` means it was something else.
+    let followed_by_word = chars.get(i).is_some_and(|c| c.is_alphanumeric());
+    (style.is_some() && !followed_by_word).then_some(i)
+}
+
+/// The value of a DMS literal in decimal degrees.
+pub fn parse_dms(text: &str) -> Option<f64> {
+    let chars: Vec<char> = text.chars().collect();
+    if scan_dms(&chars)? != chars.len() {
+        return None;
+    }
+    let (sign, body) = match text.strip_prefix('-') {
+        Some(rest) => (-1.0, rest),
+        None => (1.0, text),
+    };
+    let mut degrees = 0.0;
+    let mut scale = 1.0;
+    for part in body.split(['°', 'd', '\'', 'm', '"', 's']) {
+        if part.is_empty() {
+            continue;
+        }
+        degrees += part.parse::<f64>().ok()? * scale;
+        scale /= 60.0;
+    }
+    Some(sign * degrees)
+}
+
+/// Writes decimal degrees as `45°30'15"`, with seconds rounded to two
+/// decimals.
+pub fn format_dms(degrees: f64) -> String {
+    let sign = if degrees < 0.0 { "-" } else { "" };
+    let hundredths = (degrees.abs() * 360_000.0).round();
+    let whole_seconds = (hundredths / 100.0).floor();
+    let d = (whole_seconds / 3600.0).floor();
+    let m = ((whole_seconds - d * 3600.0) / 60.0).floor();
+    let s = (hundredths - (d * 3600.0 + m * 60.0) * 100.0) / 100.0;
+    format!("{}{}°{}'{}\"", sign, d, m, s)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scan_dms() {
+        let chars: Vec<char> = "45°30'15\" + 1".chars().collect();
+        assert_eq!(scan_dms(&chars), Some(9));
+        let chars: Vec<char> = "45d30m15s)".chars().collect();
+        assert_eq!(scan_dms(&chars), Some(9));
+        let chars: Vec<char> = "-12.5°".chars().collect();
+        assert_eq!(scan_dms(&chars), Some(6));
+        for text in ["45", "30m15s", "45d", "45days", "45°30m"] {
+            let chars: Vec<char> = text.chars().collect();
+            assert_ne!(scan_dms(&chars), Some(chars.len()), "{}", text);
+        }
+    }
+
+    #[test]
+    fn test_parse_and_format() {
+        assert_eq!(parse_dms("45°30'"), Some(45.5));
+        assert_eq!(parse_dms("45d30m36s"), Some(45.51));
+        assert_eq!(parse_dms("-10°30'"), Some(-10.5));
+        assert_eq!(format_dms(45.0 + 30.0 / 60.0 + 15.0 / 3600.0), "45°30'15\"");
+        assert_eq!(format_dms(-10.5), "-10°30'0\"");
+        assert_eq!(format_dms(29.999999999), "30°0'0\"");
+        assert_eq!(format_dms(1.0 / 3.0), "0°20'0\"");
+    }
+}
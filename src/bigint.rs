@@ -0,0 +1,123 @@
+use core::fmt;
+
+const BASE: u64 = 1_000_000_000;
+
+/// An unsigned integer of any size, stored as base-10⁹ digits with the
+/// least significant first, which keeps printing in decimal simple.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BigUint(Vec<u32>);
+
+impl BigUint {
+    pub fn from_u64(mut n: u64) -> BigUint {
+        let mut digits = vec![];
+        while n > 0 {
+            digits.push((n % BASE) as u32);
+            n /= BASE;
+        }
+        BigUint(digits)
+    }
+
+    pub fn add(&self, other: &BigUint) -> BigUint {
+        let mut digits = Vec::with_capacity(self.0.len().max(other.0.len()) + 1);
+        let mut carry = 0;
+        for i in 0..self.0.len().max(other.0.len()) {
+            let sum = carry
+                + self.0.get(i).copied().unwrap_or(0) as u64
+                + other.0.get(i).copied().unwrap_or(0) as u64;
+            digits.push((sum % BASE) as u32);
+            carry = sum / BASE;
+        }
+        if carry > 0 {
+            digits.push(carry as u32);
+        }
+        BigUint(digits)
+    }
+
+    pub fn mul_small(&self, factor: u32) -> BigUint {
+        let mut digits = Vec::with_capacity(self.0.len() + 1);
+        let mut carry = 0;
+        for &d in &self.0 {
+            let product = d as u64 * factor as u64 + carry;
+            digits.push((product % BASE) as u32);
+            carry = product / BASE;
+        }
+        while carry > 0 {
+            digits.push((carry % BASE) as u32);
+            carry /= BASE;
+        }
+        let mut result = BigUint(digits);
+        result.trim();
+        result
+    }
+
+    /// Quotient and remainder of a division by a small number.
+    pub fn div_small(&self, divisor: u32) -> (BigUint, u32) {
+        let mut digits = vec![0; self.0.len()];
+        let mut rem = 0;
+        for i in (0..self.0.len()).rev() {
+            let current = rem * BASE + self.0[i] as u64;
+            digits[i] = (current / divisor as u64) as u32;
+            rem = current % divisor as u64;
+        }
+        let mut result = BigUint(digits);
+        result.trim();
+        (result, rem as u32)
+    }
+
+    pub fn to_u64(&self) -> Option<u64> {
+        self.0
+            .iter()
+            .rev()
+            .try_fold(0u64, |acc, &d| acc.checked_mul(BASE)?.checked_add(d as u64))
+    }
+
+    /// The nearest float, which is rounded past 2^53.
+    pub fn to_f64(&self) -> f64 {
+        self.0
+            .iter()
+            .rev()
+            .fold(0.0, |acc, &d| acc * BASE as f64 + d as f64)
+    }
+
+    fn trim(&mut self) {
+        while self.0.last() == Some(&0) {
+            self.0.pop();
+        }
+    }
+}
+
+impl fmt::Display for BigUint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.0.split_last() {
+            None => write!(f, "0"),
+            Some((top, rest)) => {
+                write!(f, "{}", top)?;
+                for d in rest.iter().rev() {
+                    write!(f, "{:09}", d)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_arithmetic_and_display() {
+        let big = BigUint::from_u64(999_999_999_999);
+        assert_eq!(big.add(&BigUint::from_u64(1)).to_string(), "1000000000000");
+        let product = big.mul_small(1_000_000);
+        assert_eq!(product.to_string(), "999999999999000000");
+        assert_eq!(product.div_small(1_000_000), (big.clone(), 0));
+        assert_eq!(
+            BigUint::from_u64(17).div_small(5),
+            (BigUint::from_u64(3), 2)
+        );
+        assert_eq!(BigUint::from_u64(0).to_string(), "0");
+        assert_eq!(big.to_u64(), Some(999_999_999_999));
+        assert_eq!(big.mul_small(u32::MAX).mul_small(u32::MAX).to_u64(), None);
+    }
+}
@@ -0,0 +1,64 @@
+/// Tip and share of a bill split between several people.
+#[derive(Debug, PartialEq)]
+pub struct Split {
+    pub tip: f64,
+    pub total: f64,
+    pub per_person: f64,
+}
+
+impl Split {
+    /// The breakdown to the cent, one part per entry.
+    pub fn parts(&self) -> [String; 3] {
+        [
+            format!("Tip: {:.2}", self.tip),
+            format!("Total: {:.2}", self.total),
+            format!("Per person: {:.2}", self.per_person),
+        ]
+    }
+}
+
+/// Adds a tip to `bill` and splits the total. `rate` is a fraction
+/// (`0.18`, or `18%` in an expression).
+pub fn split_bill(bill: f64, rate: f64, people: f64) -> Result<Split, String> {
+    if bill < 0.0 {
+        return Err("The bill cannot be negative".to_string());
+    }
+    if !(0.0..=1.0).contains(&rate) {
+        return Err(format!(
+            "Tip rate {} is out of range, write it as a percentage such as 18%",
+            rate
+        ));
+    }
+    if people < 1.0 || people.fract() != 0.0 {
+        return Err(format!("Cannot split between {} people", people));
+    }
+
+    let tip = bill * rate;
+    let total = bill + tip;
+    Ok(Split {
+        tip,
+        total,
+        per_person: total / people,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_bill() {
+        let split = split_bill(84.5, 0.18, 4.0).unwrap();
+        assert!((split.tip - 15.21).abs() < 1e-9);
+        assert!((split.total - 99.71).abs() < 1e-9);
+        assert!((split.per_person - 24.9275).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_split_bill_errors() {
+        assert!(split_bill(84.5, 18.0, 4.0).is_err());
+        assert!(split_bill(84.5, 0.18, 0.0).is_err());
+        assert!(split_bill(84.5, 0.18, 2.5).is_err());
+        assert!(split_bill(-1.0, 0.18, 1.0).is_err());
+    }
+}
@@ -0,0 +1,70 @@
+//! Bit operations on fixed-width words. Negative numbers are taken in
+//! two's complement, so `popcount(-1)` counts every bit of the word.
+
+pub fn check_width(width: i64) -> Result<u32, String> {
+    if (1..=64).contains(&width) {
+        Ok(width as u32)
+    } else {
+        Err(format!("Word widths go from 1 to 64 bits, got {}", width))
+    }
+}
+
+pub fn check_bit(bit: i64, width: u32) -> Result<u32, String> {
+    if (0..width as i64).contains(&bit) {
+        Ok(bit as u32)
+    } else {
+        Err(format!(
+            "Bit {} is outside a {}-bit word (bits 0 to {})",
+            bit,
+            width,
+            width - 1
+        ))
+    }
+}
+
+fn mask(width: u32) -> u64 {
+    u64::MAX >> (64 - width)
+}
+
+/// `n` cut down to `width` bits.
+pub fn to_word(n: i64, width: u32) -> u64 {
+    n as u64 & mask(width)
+}
+
+pub fn rotl(word: u64, by: u32, width: u32) -> u64 {
+    let word = word & mask(width);
+    let by = by % width;
+    if by == 0 {
+        word
+    } else {
+        (word << by | word >> (width - by)) & mask(width)
+    }
+}
+
+pub fn rotr(word: u64, by: u32, width: u32) -> u64 {
+    rotl(word, width - by % width, width)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_words() {
+        assert_eq!(to_word(-1, 8), 0xFF);
+        assert_eq!(to_word(0x1234, 8), 0x34);
+        assert_eq!(to_word(-1, 64).count_ones(), 64);
+        assert!(check_width(0).is_err() && check_width(65).is_err());
+        assert!(check_bit(8, 8).is_err());
+        assert_eq!(check_bit(7, 8), Ok(7));
+    }
+
+    #[test]
+    fn test_rotate() {
+        assert_eq!(rotl(0b1000_0001, 1, 8), 0b0000_0011);
+        assert_eq!(rotr(0b0000_0011, 1, 8), 0b1000_0001);
+        assert_eq!(rotl(1, 64, 64), 1);
+        assert_eq!(rotr(1, 1, 64), 1 << 63);
+        assert_eq!(rotl(0b101, 2, 3), 0b110);
+    }
+}
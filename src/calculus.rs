@@ -0,0 +1,421 @@
+//! Functions that work on an expression in a named variable, such as
+//! `diff(x^2, x, 3)`. Their arguments reach them unevaluated so the
+//! expression can be sampled at many points.
+
+use crate::env::Env;
+use crate::expr::{Expr, eval};
+use crate::stats;
+use crate::value::Value;
+
+pub fn takes_expression(name: &str) -> bool {
+    matches!(
+        name,
+        "diff" | "integrate" | "mcintegrate" | "solve" | "map" | "plot"
+    )
+}
+
+/// Most random points `mcintegrate` will take.
+const MAX_SAMPLES: f64 = 1e7;
+
+pub fn call(name: &str, args: &[Expr], env: &mut Env) -> Result<Value, String> {
+    match name {
+        "diff" => {
+            if args.len() != 3 {
+                return Err(format!(
+                    "diff() takes an expression, a variable and a point, got {} argument(s)",
+                    args.len()
+                ));
+            }
+            let var = variable_arg(name, &args[1])?;
+            let x = number_at(name, &args[2], env)?;
+            // The differences never look at x itself, so check it is defined there.
+            sample(&args[0], var, x, env)?;
+            let slope = derivative(|x| sample(&args[0], var, x, env), x)?;
+            Ok(Value::Number(tidy(slope)))
+        }
+        "integrate" => {
+            if !(4..=5).contains(&args.len()) {
+                return Err(format!(
+                    "integrate() takes an expression, a variable, two bounds and an optional \
+                     tolerance, got {} argument(s)",
+                    args.len()
+                ));
+            }
+            let var = variable_arg(name, &args[1])?;
+            let a = number_at(name, &args[2], env)?;
+            let b = number_at(name, &args[3], env)?;
+            let tolerance = match args.get(4) {
+                Some(arg) => number_at(name, arg, env)?,
+                None => 1e-10,
+            };
+            if tolerance <= 0.0 {
+                return Err("integrate() needs a positive tolerance".to_string());
+            }
+            let area = integral(|x| sample(&args[0], var, x, env), a, b, tolerance)?;
+            Ok(Value::Number(tidy(area)))
+        }
+        "mcintegrate" => {
+            if !(4..=5).contains(&args.len()) {
+                return Err(format!(
+                    "mcintegrate() takes an expression, a variable, two bounds and an optional \
+                     number of samples, got {} argument(s)",
+                    args.len()
+                ));
+            }
+            let var = variable_arg(name, &args[1])?;
+            let a = number_at(name, &args[2], env)?;
+            let b = number_at(name, &args[3], env)?;
+            let samples = match args.get(4) {
+                Some(arg) => number_at(name, arg, env)?,
+                None => 10_000.0,
+            };
+            if samples.fract() != 0.0 || !(2.0..=MAX_SAMPLES).contains(&samples) {
+                return Err(format!(
+                    "mcintegrate() takes 2 to {} samples, got {}",
+                    MAX_SAMPLES, samples
+                ));
+            }
+            let mut heights = Vec::with_capacity(samples as usize);
+            for _ in 0..samples as usize {
+                let x = a + (b - a) * env.rng.uniform();
+                heights.push(sample(&args[0], var, x, env)?);
+            }
+            // The area is the width times the mean height, and so is its error.
+            let estimate = (b - a) * stats::mean(&heights)?;
+            let error = (b - a).abs() * stats::stddev(&heights)? / samples.sqrt();
+            Ok(Value::List(vec![estimate, error]))
+        }
+        "solve" => {
+            if !(2..=4).contains(&args.len()) {
+                return Err(format!(
+                    "solve() takes an expression, a variable and either a starting guess or \
+                     two bounds, got {} argument(s)",
+                    args.len()
+                ));
+            }
+            let var = variable_arg(name, &args[1])?;
+            let bounds = args[2..]
+                .iter()
+                .map(|arg| number_at(name, arg, env))
+                .collect::<Result<Vec<_>, _>>()?;
+            let mut f = |x| sample(&args[0], var, x, env);
+            let root = match bounds[..] {
+                [a, b] => {
+                    let (fa, fb) = (f(a)?, f(b)?);
+                    if fa * fb > 0.0 {
+                        return Err(format!(
+                            "solve() needs a sign change between {} and {}, but both ends are {}",
+                            a,
+                            b,
+                            if fa > 0.0 { "positive" } else { "negative" }
+                        ));
+                    }
+                    bisect(&mut f, (a, fa), (b, fb))?
+                }
+                [guess] => root_near(&mut f, guess)?,
+                _ => root_near(&mut f, 1.0)?,
+            };
+            Ok(Value::Number(root))
+        }
+        "map" => {
+            if args.len() != 3 {
+                return Err(format!(
+                    "map() takes an expression, a variable and a list, got {} argument(s)",
+                    args.len()
+                ));
+            }
+            let var = variable_arg(name, &args[1])?;
+            let items = match eval(&args[2], env)? {
+                Value::List(items) => items,
+                other => return Err(format!("map() expects a list, got {}", other.kind())),
+            };
+            items
+                .into_iter()
+                .map(|x| sample(&args[0], var, x, env))
+                .collect::<Result<Vec<_>, _>>()
+                .map(Value::List)
+        }
+        "plot" => Err("plot() draws a graph, so it must be on a line of its own".to_string()),
+        _ => Err(format!("Unknown function: {}", name)),
+    }
+}
+
+fn variable_arg<'a>(name: &str, arg: &'a Expr) -> Result<&'a str, String> {
+    match arg {
+        Expr::Name(var) => Ok(var),
+        _ => Err(format!("{}() expects a variable name such as x", name)),
+    }
+}
+
+fn number_at(name: &str, arg: &Expr, env: &mut Env) -> Result<f64, String> {
+    match eval(arg, env)? {
+        Value::Number(n) => Ok(n),
+        other => Err(format!("{}() expects a number, got {}", name, other.kind())),
+    }
+}
+
+/// Evaluates `expr` with `var` set to `x`. Whatever `var` held before is
+/// restored afterwards.
+pub fn sample(expr: &Expr, var: &str, x: f64, env: &mut Env) -> Result<f64, String> {
+    let saved = env.variables.insert(var.to_string(), Value::Number(x));
+    let result = eval(expr, env);
+    match saved {
+        Some(value) => env.variables.insert(var.to_string(), value),
+        None => env.variables.remove(var),
+    };
+    match result? {
+        Value::Number(y) if y.is_finite() => Ok(y),
+        Value::Number(_) => Err(format!("The expression is not finite at {} = {}", var, x)),
+        other => Err(format!(
+            "The expression gives a {}, not a number",
+            other.kind()
+        )),
+    }
+}
+
+/// Rounds to 12 significant digits, which is about as far as the numeric
+/// methods here can be trusted, so `6.000000000000001` prints as `6`.
+pub fn tidy(x: f64) -> f64 {
+    format!("{:.11e}", x).parse().unwrap_or(x)
+}
+
+/// Central differences with shrinking steps, extrapolated to a step of zero
+/// (Ridders' method). Stops once the estimates start to diverge again.
+fn derivative(mut f: impl FnMut(f64) -> Result<f64, String>, x: f64) -> Result<f64, String> {
+    const SHRINK: f64 = 1.4;
+    const STEPS: usize = 10;
+
+    let mut h = 0.1 * x.abs().max(1.0);
+    let mut table = [[0.0; STEPS]; STEPS];
+    table[0][0] = (f(x + h)? - f(x - h)?) / (2.0 * h);
+    let mut best = table[0][0];
+    let mut error = f64::INFINITY;
+
+    for i in 1..STEPS {
+        h /= SHRINK;
+        table[0][i] = (f(x + h)? - f(x - h)?) / (2.0 * h);
+        let mut factor = SHRINK * SHRINK;
+        for j in 1..=i {
+            table[j][i] = (table[j - 1][i] * factor - table[j - 1][i - 1]) / (factor - 1.0);
+            factor *= SHRINK * SHRINK;
+            let change = (table[j][i] - table[j - 1][i])
+                .abs()
+                .max((table[j][i] - table[j - 1][i - 1]).abs());
+            if change <= error {
+                error = change;
+                best = table[j][i];
+            }
+        }
+        if (table[i][i] - table[i - 1][i - 1]).abs() >= 2.0 * error {
+            break;
+        }
+    }
+    Ok(best)
+}
+
+/// Halves `[a, b]` until it cannot shrink any further. The function values
+/// at the ends must not have the same sign.
+fn bisect(
+    f: &mut impl FnMut(f64) -> Result<f64, String>,
+    (mut a, mut fa): (f64, f64),
+    (mut b, fb): (f64, f64),
+) -> Result<f64, String> {
+    let limit = fa.abs().max(fb.abs());
+    if fa == 0.0 {
+        return Ok(a);
+    }
+    if fb == 0.0 {
+        return Ok(b);
+    }
+    for _ in 0..200 {
+        let m = (a + b) / 2.0;
+        if m == a || m == b {
+            break;
+        }
+        let fm = f(m)?;
+        if fm == 0.0 {
+            return Ok(m);
+        }
+        if fa * fm < 0.0 {
+            b = m;
+        } else {
+            (a, fa) = (m, fm);
+        }
+    }
+    // A sign change can also come from a pole, as with 1/x at 0.
+    let root = (a + b) / 2.0;
+    match f(root) {
+        Ok(y) if y.abs() <= limit => Ok(root),
+        _ => Err(format!(
+            "solve() found a jump, not a root, near {}; the expression is discontinuous there",
+            if root.abs() < 1e-12 { 0.0 } else { tidy(root) }
+        )),
+    }
+}
+
+/// Looks for a sign change in steps growing outwards from `guess` and
+/// bisects it. Roots that only touch zero (like x² at 0) have no sign
+/// change, so Newton's method from `guess` is the fallback.
+fn root_near(f: &mut impl FnMut(f64) -> Result<f64, String>, guess: f64) -> Result<f64, String> {
+    let f0 = f(guess)?;
+    if f0 == 0.0 {
+        return Ok(guess);
+    }
+    let mut step = 0.1 * guess.abs().max(1.0);
+    let (mut left, mut right) = ((guess, f0), (guess, f0));
+    for _ in 0..60 {
+        for (end, x) in [(&mut right, guess + step), (&mut left, guess - step)] {
+            // Points where the expression is undefined are skipped.
+            let Ok(fx) = f(x) else { continue };
+            if fx * end.1 <= 0.0 {
+                return bisect(f, *end, (x, fx));
+            }
+            *end = (x, fx);
+        }
+        step *= 2.0;
+    }
+    newton(f, guess)
+}
+
+fn newton(f: &mut impl FnMut(f64) -> Result<f64, String>, mut x: f64) -> Result<f64, String> {
+    let start = x;
+    for _ in 0..200 {
+        let fx = f(x)?;
+        if fx.abs() < 1e-300 {
+            return Ok(x);
+        }
+        let h = 1e-6 * x.abs().max(1.0);
+        let slope = (f(x + h)? - f(x - h)?) / (2.0 * h);
+        if slope == 0.0 {
+            break;
+        }
+        let next = x - fx / slope;
+        if (next - x).abs() <= 1e-15 * x.abs().max(1.0) {
+            return Ok(next);
+        }
+        x = next;
+    }
+    if f(x)?.abs() < 1e-12 {
+        Ok(x)
+    } else {
+        Err(format!(
+            "solve() found no root starting from {}; try another guess or give two bounds",
+            start
+        ))
+    }
+}
+
+/// Adaptive Simpson quadrature: intervals are halved until the two halves
+/// agree with the whole to within `tolerance`, taken relative to the size
+/// of the integral once that is above 1.
+fn integral(
+    mut f: impl FnMut(f64) -> Result<f64, String>,
+    a: f64,
+    b: f64,
+    tolerance: f64,
+) -> Result<f64, String> {
+    if a == b {
+        return Ok(0.0);
+    }
+    let (fa, fb) = (f(a)?, f(b)?);
+    let m = (a + b) / 2.0;
+    let fm = f(m)?;
+    let whole = (b - a) / 6.0 * (fa + 4.0 * fm + fb);
+    let tolerance = tolerance * whole.abs().max(1.0);
+    simpson(&mut f, (a, fa), (m, fm), (b, fb), whole, tolerance, 50)
+}
+
+fn simpson(
+    f: &mut impl FnMut(f64) -> Result<f64, String>,
+    (a, fa): (f64, f64),
+    (m, fm): (f64, f64),
+    (b, fb): (f64, f64),
+    whole: f64,
+    tolerance: f64,
+    depth: u32,
+) -> Result<f64, String> {
+    let (lm, rm) = ((a + m) / 2.0, (m + b) / 2.0);
+    let (flm, frm) = (f(lm)?, f(rm)?);
+    let left = (m - a) / 6.0 * (fa + 4.0 * flm + fm);
+    let right = (b - m) / 6.0 * (fm + 4.0 * frm + fb);
+    let delta = left + right - whole;
+    if delta.abs() <= 15.0 * tolerance {
+        return Ok(left + right + delta / 15.0);
+    }
+    if depth == 0 {
+        return Err(format!(
+            "integrate() did not converge near x = {}; the function may be singular there",
+            m
+        ));
+    }
+    Ok(simpson(
+        f,
+        (a, fa),
+        (lm, flm),
+        (m, fm),
+        left,
+        tolerance / 2.0,
+        depth - 1,
+    )? + simpson(
+        f,
+        (m, fm),
+        (rm, frm),
+        (b, fb),
+        right,
+        tolerance / 2.0,
+        depth - 1,
+    )?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_derivative() {
+        let slope = derivative(|x| Ok(x.powi(3)), 2.0).unwrap();
+        assert!((slope - 12.0).abs() < 1e-10);
+        let slope = derivative(|x| Ok(x.exp()), 1.0).unwrap();
+        assert!((slope - std::f64::consts::E).abs() < 1e-10);
+    }
+
+    #[test]
+    fn test_integral() {
+        let area = integral(|x| Ok(x * x), 0.0, 3.0, 1e-10).unwrap();
+        assert!((area - 9.0).abs() < 1e-10);
+        let area = integral(|x| Ok(x.sin()), 0.0, std::f64::consts::PI, 1e-10).unwrap();
+        assert!((area - 2.0).abs() < 1e-9);
+        let backwards = integral(Ok, 2.0, 0.0, 1e-10).unwrap();
+        assert!((backwards + 2.0).abs() < 1e-12);
+        assert_eq!(integral(Ok, 1.0, 1.0, 1e-10).unwrap(), 0.0);
+        // A large smooth integral converges to the same relative precision.
+        let area = integral(|x| Ok(x.exp()), 0.0, 30.0, 1e-10).unwrap();
+        let exact = 30f64.exp() - 1.0;
+        assert!((area - exact).abs() < 1e-9 * exact);
+    }
+
+    #[test]
+    fn test_root_finding() {
+        let root = bisect(&mut |x| Ok(x * x - 2.0), (0.0, -2.0), (2.0, 2.0)).unwrap();
+        assert!((root - std::f64::consts::SQRT_2).abs() < 1e-15);
+        let root = root_near(&mut |x| Ok(x * x - 7.0), 1.0).unwrap();
+        assert!((root - 7f64.sqrt()).abs() < 1e-15);
+        let root = root_near(&mut |x| Ok(x * x), 1.0).unwrap();
+        assert!(root.abs() < 1e-6);
+        assert!(root_near(&mut |x| Ok(x * x + 1.0), 1.0).is_err());
+        assert!(bisect(&mut |x| Ok(1.0 / x), (-1.0, -1.0), (2.0, 0.5)).is_err());
+    }
+
+    #[test]
+    fn test_sample_restores_variable() {
+        let mut env = Env::default();
+        env.variables.insert("x".into(), Value::Number(7.0));
+        let expr = Expr::Binary(
+            '*',
+            Box::new(Expr::Name("x".into())),
+            Box::new(Expr::Literal(Value::Number(2.0))),
+        );
+        assert_eq!(sample(&expr, "x", 3.0, &mut env).unwrap(), 6.0);
+        assert_eq!(env.variables["x"], Value::Number(7.0));
+    }
+}
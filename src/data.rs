@@ -0,0 +1,91 @@
+use std::fs;
+
+/// Reads `column` from CSV text: a 1-based index, or a header name. Every
+/// row after the header must have a number there, so that columns loaded
+/// one after the other stay lined up row for row.
+pub fn parse_column(content: &str, column: &str) -> Result<Vec<f64>, String> {
+    let mut lines = content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty());
+    let Some((_, first)) = lines.next() else {
+        return Err("The file is empty".to_string());
+    };
+    let first_cells: Vec<&str> = first.split(',').map(str::trim).collect();
+
+    let index = match column.parse::<usize>() {
+        Ok(0) => return Err("Columns are numbered from 1".to_string()),
+        Ok(n) => n - 1,
+        Err(_) => first_cells
+            .iter()
+            .position(|cell| cell.trim_matches('"') == column)
+            .ok_or_else(|| format!("No column named '{}'", column))?,
+    };
+
+    let mut values = Vec::new();
+    let header_cell = first_cells.get(index).copied().unwrap_or("");
+    if let Ok(n) = header_cell.parse::<f64>() {
+        values.push(n);
+    }
+    for (number, line) in lines {
+        let cell = line.split(',').nth(index).unwrap_or("").trim();
+        match cell.parse::<f64>() {
+            Ok(n) => values.push(n),
+            Err(_) => {
+                return Err(format!(
+                    "Line {} has no number in column {} (found '{}')",
+                    number + 1,
+                    column,
+                    cell
+                ));
+            }
+        }
+    }
+
+    if values.is_empty() {
+        return Err(format!("Column {} has no numbers", column));
+    }
+    Ok(values)
+}
+
+pub fn load_column(path: &str, column: &str) -> Result<Vec<f64>, String> {
+    let content = fs::read_to_string(path).map_err(|e| format!("Cannot read {}: {}", path, e))?;
+    parse_column(&content, column)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CSV: &str = "name,score,time\nann,12.5,30\nbob,11,31\n\ncid,9,29\n";
+
+    #[test]
+    fn test_parse_column_by_index_and_name() {
+        assert_eq!(parse_column(CSV, "2").unwrap(), vec![12.5, 11.0, 9.0]);
+        assert_eq!(parse_column(CSV, "time").unwrap(), vec![30.0, 31.0, 29.0]);
+    }
+
+    #[test]
+    fn test_parse_column_without_header() {
+        assert_eq!(parse_column("1,2\n3,4\n", "1").unwrap(), vec![1.0, 3.0]);
+    }
+
+    #[test]
+    fn test_non_numeric_cell_is_an_error() {
+        // Skipping the row here but not in column 1 would misalign the two.
+        let csv = "x,y\n1,10\n2,\n3,30\n";
+        assert_eq!(
+            parse_column(csv, "y").unwrap_err(),
+            "Line 3 has no number in column y (found '')"
+        );
+        assert!(parse_column("x,y\n1,10\nn/a,20\n", "1").is_err());
+    }
+
+    #[test]
+    fn test_parse_column_errors() {
+        assert!(parse_column(CSV, "0").is_err());
+        assert!(parse_column(CSV, "1").is_err());
+        assert!(parse_column(CSV, "missing").is_err());
+        assert!(parse_column("", "1").is_err());
+    }
+}
@@ -0,0 +1,224 @@
+use std::f64::consts::{PI, SQRT_2};
+
+/// Error function: Taylor series near zero, continued fraction for the tail.
+pub fn erf(x: f64) -> f64 {
+    if x < 0.0 {
+        return -erf(-x);
+    }
+    if x < 2.5 {
+        let mut term = x;
+        let mut sum = x;
+        let mut n = 0.0;
+        while term.abs() > 1e-17 * sum.abs() {
+            n += 1.0;
+            term *= -x * x / n;
+            sum += term / (2.0 * n + 1.0);
+        }
+        2.0 / PI.sqrt() * sum
+    } else {
+        1.0 - erfc_tail(x)
+    }
+}
+
+// erfc(x) for x >= 2.5 from its continued fraction, evaluated bottom-up.
+fn erfc_tail(x: f64) -> f64 {
+    let mut f = x;
+    for n in (1..=60).rev() {
+        f = x + (n as f64 / 2.0) / f;
+    }
+    (-x * x).exp() / (PI.sqrt() * f)
+}
+
+/// P(X <= x) for X ~ N(mean, sigma²).
+pub fn normcdf(x: f64, mean: f64, sigma: f64) -> Result<f64, String> {
+    if sigma <= 0.0 {
+        return Err("normcdf() needs sigma > 0".to_string());
+    }
+    let z = (x - mean) / (sigma * SQRT_2);
+    if z < -2.5 {
+        Ok(erfc_tail(-z) / 2.0)
+    } else {
+        Ok((1.0 + erf(z)) / 2.0)
+    }
+}
+
+/// Inverse of [`normcdf`]: Acklam's rational approximation polished
+/// with Newton steps.
+pub fn norminv(p: f64, mean: f64, sigma: f64) -> Result<f64, String> {
+    if !(p > 0.0 && p < 1.0) {
+        return Err("norminv() needs 0 < p < 1".to_string());
+    }
+    if sigma <= 0.0 {
+        return Err("norminv() needs sigma > 0".to_string());
+    }
+
+    const A: [f64; 6] = [
+        -3.969683028665376e1,
+        2.209460984245205e2,
+        -2.759285104469687e2,
+        1.383_577_518_672_69e2,
+        -3.066479806614716e1,
+        2.506628277459239,
+    ];
+    const B: [f64; 5] = [
+        -5.447609879822406e1,
+        1.615858368580409e2,
+        -1.556989798598866e2,
+        6.680131188771972e1,
+        -1.328068155288572e1,
+    ];
+    const C: [f64; 6] = [
+        -7.784894002430293e-3,
+        -3.223964580411365e-1,
+        -2.400758277161838,
+        -2.549732539343734,
+        4.374664141464968,
+        2.938163982698783,
+    ];
+    const D: [f64; 4] = [
+        7.784695709041462e-3,
+        3.224671290700398e-1,
+        2.445134137142996,
+        3.754408661907416,
+    ];
+
+    let tail = |q: f64| {
+        (((((C[0] * q + C[1]) * q + C[2]) * q + C[3]) * q + C[4]) * q + C[5])
+            / ((((D[0] * q + D[1]) * q + D[2]) * q + D[3]) * q + 1.0)
+    };
+    let mut z = if p < 0.02425 {
+        tail((-2.0 * p.ln()).sqrt())
+    } else if p > 1.0 - 0.02425 {
+        -tail((-2.0 * (1.0 - p).ln()).sqrt())
+    } else {
+        let q = p - 0.5;
+        let r = q * q;
+        (((((A[0] * r + A[1]) * r + A[2]) * r + A[3]) * r + A[4]) * r + A[5]) * q
+            / (((((B[0] * r + B[1]) * r + B[2]) * r + B[3]) * r + B[4]) * r + 1.0)
+    };
+
+    for _ in 0..2 {
+        let error = normcdf(z, 0.0, 1.0)? - p;
+        let density = (-z * z / 2.0).exp() / (2.0 * PI).sqrt();
+        z -= error / density;
+    }
+    Ok(mean + sigma * z)
+}
+
+fn ln_factorial(n: u64) -> f64 {
+    (2..=n).map(|i| (i as f64).ln()).sum()
+}
+
+fn check_count(name: &str, value: f64) -> Result<u64, String> {
+    if value < 0.0 || value.fract() != 0.0 || value > 1e7 {
+        Err(format!(
+            "{}() needs a whole number of events, got {}",
+            name, value
+        ))
+    } else {
+        Ok(value as u64)
+    }
+}
+
+/// P(X = k) for X ~ Binomial(n, p), argument order as on TI calculators.
+pub fn binompdf(n: f64, p: f64, k: f64) -> Result<f64, String> {
+    let n = check_count("binompdf", n)?;
+    let k = check_count("binompdf", k)?;
+    if !(0.0..=1.0).contains(&p) {
+        return Err("binompdf() needs 0 <= p <= 1".to_string());
+    }
+    if k > n {
+        return Ok(0.0);
+    }
+    if p == 0.0 || p == 1.0 {
+        let certain = if p == 0.0 { 0 } else { n };
+        return Ok(if k == certain { 1.0 } else { 0.0 });
+    }
+    if n <= 170 {
+        // Small enough to work directly, which avoids rounding in exp/ln.
+        let choose = (1..=k).fold(1.0, |acc, i| acc * (n - k + i) as f64 / i as f64);
+        return Ok(choose * p.powi(k as i32) * (1.0 - p).powi((n - k) as i32));
+    }
+    let ln_choose = ln_factorial(n) - ln_factorial(k) - ln_factorial(n - k);
+    Ok((ln_choose + k as f64 * p.ln() + (n - k) as f64 * (1.0 - p).ln()).exp())
+}
+
+/// P(X = k) for X ~ Poisson(lambda).
+pub fn poissonpdf(lambda: f64, k: f64) -> Result<f64, String> {
+    let k = check_count("poissonpdf", k)?;
+    if lambda <= 0.0 {
+        return Err("poissonpdf() needs lambda > 0".to_string());
+    }
+    Ok((k as f64 * lambda.ln() - lambda - ln_factorial(k)).exp())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f64, b: f64, tolerance: f64) -> bool {
+        (a - b).abs() < tolerance
+    }
+
+    #[test]
+    fn test_erf() {
+        assert!(close(erf(0.5), 0.520_499_877_813_046_5, 1e-14));
+        assert!(close(erf(3.0), 0.999_977_909_503_001_4, 1e-14));
+        assert_eq!(erf(0.0), 0.0);
+    }
+
+    #[test]
+    fn test_normcdf() {
+        assert!(close(
+            normcdf(1.96, 0.0, 1.0).unwrap(),
+            0.975_002_104_851_780,
+            1e-12
+        ));
+        assert!(close(
+            normcdf(110.0, 100.0, 15.0).unwrap(),
+            0.747_507_462_453_077,
+            1e-12
+        ));
+        assert!(close(
+            normcdf(-5.0, 0.0, 1.0).unwrap(),
+            2.866_515_718_791_939e-7,
+            1e-18
+        ));
+        assert!(normcdf(0.0, 0.0, 0.0).is_err());
+    }
+
+    #[test]
+    fn test_norminv() {
+        assert!(close(
+            norminv(0.975, 0.0, 1.0).unwrap(),
+            1.959_963_984_540_054,
+            1e-9
+        ));
+        assert!(close(norminv(0.5, 100.0, 15.0).unwrap(), 100.0, 1e-9));
+        assert!(close(
+            norminv(1e-6, 0.0, 1.0).unwrap(),
+            -4.753_424_308_822_899,
+            1e-8
+        ));
+        assert!(norminv(1.0, 0.0, 1.0).is_err());
+    }
+
+    #[test]
+    fn test_discrete_distributions() {
+        assert!(close(binompdf(10.0, 0.5, 3.0).unwrap(), 0.117_187_5, 1e-12));
+        assert_eq!(binompdf(5.0, 0.3, 6.0).unwrap(), 0.0);
+        assert_eq!(binompdf(4.0, 1.0, 4.0).unwrap(), 1.0);
+        assert!(close(
+            binompdf(1000.0, 0.5, 500.0).unwrap(),
+            0.025_225_018_178_360_1,
+            1e-12
+        ));
+        assert!(close(
+            poissonpdf(3.0, 2.0).unwrap(),
+            0.224_041_807_655_388_1,
+            1e-12
+        ));
+        assert!(binompdf(10.0, 0.5, 2.5).is_err());
+        assert!(poissonpdf(0.0, 1.0).is_err());
+    }
+}
@@ -0,0 +1,102 @@
+//! Divisor functions for number theory: `divisors`, `sigma` and `totient`.
+
+/// Largest n accepted, so trial division stays quick and every result fits
+/// in a float exactly.
+pub const MAX_N: u64 = 1_000_000_000_000;
+
+fn check(name: &str, n: i64) -> Result<u64, String> {
+    match u64::try_from(n) {
+        Ok(n) if (1..=MAX_N).contains(&n) => Ok(n),
+        Ok(n) if n > MAX_N => Err(format!("{}() is limited to n <= {}", name, MAX_N)),
+        _ => Err(format!("{}() needs n >= 1, got {}", name, n)),
+    }
+}
+
+/// Prime factors of n with their powers, smallest first.
+fn factorize(mut n: u64) -> Vec<(u64, u32)> {
+    let mut factors = Vec::new();
+    let mut p = 2;
+    while p * p <= n {
+        let mut power = 0;
+        while n.is_multiple_of(p) {
+            n /= p;
+            power += 1;
+        }
+        if power > 0 {
+            factors.push((p, power));
+        }
+        p += if p == 2 { 1 } else { 2 };
+    }
+    if n > 1 {
+        factors.push((n, 1));
+    }
+    factors
+}
+
+/// Every divisor of n in increasing order.
+pub fn divisors(n: i64) -> Result<Vec<u64>, String> {
+    let n = check("divisors", n)?;
+    let mut found = vec![1];
+    for (p, power) in factorize(n) {
+        let mut more = Vec::new();
+        let mut p_k = 1;
+        for _ in 0..power {
+            p_k *= p;
+            more.extend(found.iter().map(|d| d * p_k));
+        }
+        found.extend(more);
+    }
+    found.sort_unstable();
+    Ok(found)
+}
+
+/// The sum of the divisors of n. This stays below 5n for every n allowed,
+/// but a prime factor can be large enough that `p^(k+1)` does not fit in
+/// u64, so the sums are worked out in u128.
+pub fn sigma(n: i64) -> Result<u64, String> {
+    let n = check("sigma", n)?;
+    let sum: u128 = factorize(n)
+        .into_iter()
+        .map(|(p, power)| {
+            let p = p as u128;
+            (p.pow(power + 1) - 1) / (p - 1)
+        })
+        .product();
+    u64::try_from(sum).map_err(|_| format!("sigma({}) is too large", n))
+}
+
+/// Euler's totient: how many of 1..=n share no factor with n.
+pub fn totient(n: i64) -> Result<u64, String> {
+    let n = check("totient", n)?;
+    Ok(factorize(n)
+        .into_iter()
+        .map(|(p, power)| p.pow(power - 1) * (p - 1))
+        .product())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_divisor_functions() {
+        assert_eq!(divisors(12).unwrap(), [1, 2, 3, 4, 6, 12]);
+        assert_eq!(divisors(1).unwrap(), [1]);
+        assert_eq!(divisors(97).unwrap(), [1, 97]);
+        assert_eq!(sigma(12).unwrap(), 28);
+        assert_eq!(sigma(1).unwrap(), 1);
+        assert_eq!(sigma(999_999_999_989).unwrap(), 999_999_999_990);
+        assert_eq!(totient(999_999_999_989).unwrap(), 999_999_999_988);
+        assert_eq!(totient(36).unwrap(), 12);
+        assert_eq!(totient(1).unwrap(), 1);
+        assert_eq!(totient(MAX_N as i64).unwrap(), 400_000_000_000);
+        assert_eq!(divisors(MAX_N as i64).unwrap().len(), 169);
+    }
+
+    #[test]
+    fn test_limits() {
+        assert!(divisors(0).is_err());
+        assert!(sigma(-6).is_err());
+        assert!(totient(MAX_N as i64 + 1).is_err());
+    }
+}
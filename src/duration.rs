@@ -0,0 +1,179 @@
+/// Length in chars of a duration literal at the start of `chars`, if any.
+///
+/// Accepts unit form (`1h30m`, `90min`, `2m15s`, `45sec`) and clock form
+/// (`1:30:00` as h:mm:ss, `4:30` as m:ss). A bare `45s` is not a duration,
+/// so the postfix `s` (square root) keeps working on plain numbers; seconds
+/// on their own are written `45sec`.
+pub fn scan_duration(chars: &[char]) -> Option<usize> {
+    scan_units(chars).or_else(|| scan_clock(chars))
+}
+
+fn scan_digits(chars: &[char], start: usize) -> usize {
+    let mut i = start;
+    while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+        i += 1;
+    }
+    i
+}
+
+fn scan_units(chars: &[char]) -> Option<usize> {
+    let mut i = 0;
+    let mut stands_alone = false;
+    let mut parts = 0;
+
+    loop {
+        let end = scan_digits(chars, i);
+        if end == i {
+            break;
+        }
+        let rest: String = chars[end..].iter().take(3).collect();
+        let unit_len = if rest.starts_with("min") || rest == "sec" {
+            stands_alone = true;
+            3
+        } else if rest.starts_with('h') || rest.starts_with('m') {
+            stands_alone = true;
+            1
+        } else if rest.starts_with('s') {
+            1
+        } else {
+            break;
+        };
+        i = end + unit_len;
+        parts += 1;
+    }
+
+    let followed_by_word = chars.get(i).is_some_and(|c| c.is_alphanumeric());
+    if parts > 0 && stands_alone && !followed_by_word {
+        Some(i)
+    } else {
+        None
+    }
+}
+
+fn scan_clock(chars: &[char]) -> Option<usize> {
+    let mut i = scan_digits(chars, 0);
+    if i == 0 {
+        return None;
+    }
+    let mut groups = 1;
+    while groups < 3 && chars.get(i) == Some(&':') {
+        let end = scan_digits(chars, i + 1);
+        if end == i + 1 {
+            return None;
+        }
+        i = end;
+        groups += 1;
+    }
+    if groups > 1 { Some(i) } else { None }
+}
+
+/// Parses a duration literal (see [`scan_duration`]) into seconds.
+pub fn parse_duration(text: &str) -> Option<f64> {
+    let (sign, body) = match text.strip_prefix('-') {
+        Some(rest) => (-1.0, rest),
+        None => (1.0, text),
+    };
+    let chars: Vec<char> = body.chars().collect();
+    if scan_duration(&chars)? != chars.len() {
+        return None;
+    }
+
+    if body.contains(':') {
+        let parts: Vec<f64> = body
+            .split(':')
+            .map(|p| p.parse::<f64>().ok())
+            .collect::<Option<_>>()?;
+        let seconds = match parts.as_slice() {
+            [m, s] => m * 60.0 + s,
+            [h, m, s] => h * 3600.0 + m * 60.0 + s,
+            _ => return None,
+        };
+        return Some(sign * seconds);
+    }
+
+    let mut total = 0.0;
+    let mut number = String::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let ch = chars[i];
+        if ch.is_ascii_digit() || ch == '.' {
+            number.push(ch);
+            i += 1;
+            continue;
+        }
+        let value = number.parse::<f64>().ok()?;
+        number.clear();
+        let (factor, len) = if body[i..].starts_with("min") {
+            (60.0, 3)
+        } else if body[i..].starts_with("sec") {
+            (1.0, 3)
+        } else {
+            match ch {
+                'h' => (3600.0, 1),
+                'm' => (60.0, 1),
+                's' => (1.0, 1),
+                _ => return None,
+            }
+        };
+        total += value * factor;
+        i += len;
+    }
+    Some(sign * total)
+}
+
+/// Formats seconds as `h:mm:ss`, keeping up to three decimals of seconds.
+pub fn format_duration(seconds: f64) -> String {
+    let sign = if seconds < 0.0 { "-" } else { "" };
+    let millis = (seconds.abs() * 1000.0).round() as u64;
+    let hours = millis / 3_600_000;
+    let minutes = millis / 60_000 % 60;
+    let secs = millis / 1000 % 60;
+    let frac = millis % 1000;
+
+    let mut out = format!("{}{}:{:02}:{:02}", sign, hours, minutes, secs);
+    if frac > 0 {
+        let digits = format!("{:03}", frac);
+        out.push('.');
+        out.push_str(digits.trim_end_matches('0'));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scan(text: &str) -> Option<usize> {
+        scan_duration(&text.chars().collect::<Vec<char>>())
+    }
+
+    #[test]
+    fn test_scan_duration() {
+        assert_eq!(scan("1h30m + 45m"), Some(5));
+        assert_eq!(scan("90min/4"), Some(5));
+        assert_eq!(scan("1:30:00"), Some(7));
+        assert_eq!(scan("45s"), None);
+        assert_eq!(scan("45sec + 1"), Some(5));
+        assert_eq!(scan("45secs"), None);
+        assert_eq!(scan("12"), None);
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("1h30m"), Some(5400.0));
+        assert_eq!(parse_duration("2m15s"), Some(135.0));
+        assert_eq!(parse_duration("30sec"), Some(30.0));
+        assert_eq!(parse_duration("-90min"), Some(-5400.0));
+        assert_eq!(parse_duration("4:30"), Some(270.0));
+        assert_eq!(parse_duration("1:02:03"), Some(3723.0));
+        assert_eq!(parse_duration("abc"), None);
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(8100.0), "2:15:00");
+        assert_eq!(format_duration(1350.0), "0:22:30");
+        assert_eq!(format_duration(7.5), "0:00:07.5");
+        assert_eq!(format_duration(-900.0), "-0:15:00");
+    }
+}
@@ -0,0 +1,38 @@
+use crate::random::Rng;
+use crate::settings::Settings;
+use crate::value::Value;
+use std::collections::HashMap;
+
+/// Where a variable was defined, for `show`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Origin {
+    /// The command that set it, e.g. `data load sales.csv col=2`.
+    pub command: String,
+    /// Whether it was set while reading `.calcrc`.
+    pub in_rc: bool,
+}
+
+/// Session state the evaluator can read and update.
+#[derive(Debug, Clone, Default)]
+pub struct Env {
+    pub settings: Settings,
+    /// Named values, such as columns loaded with `data load`.
+    pub variables: HashMap<String, Value>,
+    pub origins: HashMap<String, Origin>,
+    pub rng: Rng,
+    /// The desk-calculator memory register, kept apart from the variables:
+    /// `M+` and `M-` change it, `MR` reads it and `MC` clears it.
+    pub memory: f64,
+}
+
+impl Env {
+    /// Binds a variable, remembering which command defined it.
+    pub fn define(&mut self, name: &str, value: Value, command: &str) {
+        self.variables.insert(name.to_string(), value);
+        let origin = Origin {
+            command: command.to_string(),
+            in_rc: false,
+        };
+        self.origins.insert(name.to_string(), origin);
+    }
+}
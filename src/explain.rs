@@ -0,0 +1,117 @@
+//! Step-by-step evaluation for the `explain` command. Each step evaluates
+//! the next node the evaluator would reach (innermost first, left to right)
+//! and prints the expression with that part replaced by its value.
+
+use crate::calculus;
+use crate::env::Env;
+use crate::expr::{Expr, eval, percent_change};
+
+fn describe(expr: &Expr) -> &'static str {
+    match expr {
+        Expr::Binary('+', _, _) if percent_change(expr).is_some() => "add percent",
+        Expr::Binary('-', _, _) if percent_change(expr).is_some() => "less percent",
+        Expr::Name(_) => "variable",
+        Expr::Unary('-', _) => "negate",
+        Expr::Unary('%', _) => "percent",
+        Expr::Unary('°', _) => "angle",
+        Expr::Unary(_, _) => "square root",
+        Expr::Binary('+', _, _) => "add",
+        Expr::Binary('-', _, _) => "subtract",
+        Expr::Binary('*', _, _) => "multiply",
+        Expr::Binary('/', _, _) => "divide",
+        Expr::Binary('^', _, _) => "power",
+        Expr::Binary('\\', _, _) => "floor divide",
+        Expr::Binary(_, _, _) => "modulo",
+        Expr::Call(name, _) if name == "list" => "list",
+        Expr::Call(_, _) => "function",
+        Expr::Literal(_) => "value",
+    }
+}
+
+/// Evaluates the first node whose operands are all values and replaces it
+/// with the result. Returns what was done, or `None` once `expr` is a value.
+fn reduce_next(expr: &mut Expr, env: &mut Env) -> Result<Option<String>, String> {
+    let operands: Vec<&mut Expr> = match expr {
+        Expr::Literal(_) => return Ok(None),
+        // `x + p%` is one step once `x` and `p` are values.
+        Expr::Binary('+' | '-', left, right) if matches!(**right, Expr::Unary('%', _)) => {
+            let Expr::Unary(_, rate) = right.as_mut() else {
+                unreachable!()
+            };
+            vec![left.as_mut(), rate.as_mut()]
+        }
+        Expr::Name(_) => vec![],
+        // These take their arguments unevaluated, so they are one step.
+        Expr::Call(name, _) if calculus::takes_expression(name) => vec![],
+        Expr::Unary(_, operand) => vec![operand.as_mut()],
+        Expr::Binary(_, left, right) => vec![left.as_mut(), right.as_mut()],
+        Expr::Call(_, args) => args.iter_mut().collect(),
+    };
+    for operand in operands {
+        if let Some(step) = reduce_next(operand, env)? {
+            return Ok(Some(step));
+        }
+    }
+    let value = eval(expr, env)?;
+    let step = format!("{:<13}{} = {}", describe(expr), expr, value);
+    *expr = Expr::Literal(value);
+    Ok(Some(step))
+}
+
+pub fn explain(mut expr: Expr, env: &mut Env) -> Result<Vec<String>, String> {
+    let mut lines = vec![expr.to_string()];
+    let mut steps = Vec::new();
+    while let Some(step) = reduce_next(&mut expr, env)? {
+        steps.push((expr.to_string(), step));
+    }
+    if steps.is_empty() {
+        lines.push(format!("  = {}", expr));
+    }
+    let width = steps
+        .iter()
+        .map(|(shown, _)| shown.len())
+        .max()
+        .unwrap_or(0);
+    for (shown, step) in steps {
+        lines.push(format!("  = {:<width$}   {}", shown, step));
+    }
+    Ok(lines)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::value::Value;
+
+    fn num(n: f64) -> Box<Expr> {
+        Box::new(Expr::Literal(Value::Number(n)))
+    }
+
+    #[test]
+    fn test_steps_follow_evaluation_order() {
+        // 2 * 3 + 4 ^ 2
+        let expr = Expr::Binary(
+            '+',
+            Box::new(Expr::Binary('*', num(2.0), num(3.0))),
+            Box::new(Expr::Binary('^', num(4.0), num(2.0))),
+        );
+        let lines = explain(expr, &mut Env::default()).unwrap();
+        assert_eq!(
+            lines,
+            vec![
+                "2*3 + 4^2",
+                "  = 6 + 4^2   multiply     2*3 = 6",
+                "  = 6 + 16    power        4^2 = 16",
+                "  = 22        add          6 + 16 = 22",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_single_value_and_errors() {
+        let lines = explain(*num(5.0), &mut Env::default()).unwrap();
+        assert_eq!(lines, vec!["5", "  = 5"]);
+        let unknown = Expr::Binary('+', num(1.0), Box::new(Expr::Name("y".into())));
+        assert!(explain(unknown, &mut Env::default()).is_err());
+    }
+}
@@ -0,0 +1,31 @@
+use std::{fs, io::Write};
+
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Writes records (header first) to `path` as CSV.
+pub fn write_csv(path: &str, records: &[Vec<String>]) -> Result<(), String> {
+    let mut file = fs::File::create(path).map_err(|e| format!("Cannot create file: {}", e))?;
+    for record in records {
+        let line: Vec<String> = record.iter().map(|f| csv_field(f)).collect();
+        writeln!(file, "{}", line.join(",")).map_err(|e| format!("Write Error: {}", e))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_csv_field_quoting() {
+        assert_eq!(csv_field("12.50"), "12.50");
+        assert_eq!(csv_field("1,5"), "\"1,5\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+    }
+}
@@ -0,0 +1,225 @@
+use crate::calculus;
+use crate::env::Env;
+use crate::functions::{call_function, constant};
+use crate::precedence;
+use crate::value::{Value, apply_unary, apply_values};
+use core::fmt;
+
+/// Expression tree built by the shunting-yard parser.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Literal(Value),
+    Name(String),
+    Unary(char, Box<Expr>),
+    Binary(char, Box<Expr>, Box<Expr>),
+    Call(String, Vec<Expr>),
+}
+
+/// How tightly an expression binds, on the parser's `precedence` scale.
+/// Postfix percent counts as loosest so it is always wrapped in
+/// parentheses inside a larger expression, where `50% x` would read as
+/// modulo.
+fn binding(expr: &Expr) -> u8 {
+    match expr {
+        Expr::Binary(op, _, _) => precedence(*op),
+        Expr::Unary('-', _) => precedence('~'),
+        Expr::Unary('%', _) => 0,
+        Expr::Unary('°', _) => u8::MAX,
+        Expr::Unary(op, _) => precedence(*op),
+        Expr::Literal(Value::Number(n)) if *n < 0.0 => precedence('~'),
+        _ => u8::MAX,
+    }
+}
+
+/// Writes `expr`, in parentheses if it binds looser than `min`.
+fn write_operand(f: &mut fmt::Formatter, expr: &Expr, min: u8) -> fmt::Result {
+    if binding(expr) < min {
+        write!(f, "({})", expr)
+    } else {
+        write!(f, "{}", expr)
+    }
+}
+
+/// Prints the expression so it parses back to the same tree, with only the
+/// parentheses that precedence requires.
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Expr::Literal(Value::Text(text)) => write!(f, "\"{}\"", text),
+            // Date literals are typed without the " UTC" that results show.
+            Expr::Literal(value @ Value::Time(_)) => {
+                write!(f, "{}", value.to_string().trim_end_matches(" UTC"))
+            }
+            Expr::Literal(value) => write!(f, "{}", value),
+            Expr::Name(name) => write!(f, "{}", name),
+            Expr::Unary('-', operand) => {
+                write!(f, "-")?;
+                write_operand(f, operand, precedence('~'))
+            }
+            Expr::Unary('%', operand) => {
+                // Postfix `%` grabs only the token right before it.
+                write_operand(f, operand, u8::MAX)?;
+                write!(f, "%")
+            }
+            Expr::Unary('°', operand) => write!(f, "{}°", operand),
+            Expr::Unary(op, operand) => {
+                write_operand(f, operand, precedence(*op))?;
+                write!(f, " {}", op)
+            }
+            Expr::Binary(op, left, right) => {
+                let level = precedence(*op);
+                write_operand(f, left, level)?;
+                match op {
+                    '+' | '-' | '%' => write!(f, " {} ", op)?,
+                    '\\' => write!(f, "//")?,
+                    _ => write!(f, "{}", op)?,
+                }
+                if percent_change(self).is_some() {
+                    return write!(f, "{}", right);
+                }
+                // Operators group to the left, so an equal right operand needs parentheses.
+                write_operand(f, right, level + 1)
+            }
+            Expr::Call(name, args) => {
+                let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+                if name == "list" {
+                    write!(f, "[{}]", args.join(", "))
+                } else {
+                    write!(f, "{}({})", name, args.join(", "))
+                }
+            }
+        }
+    }
+}
+
+/// How an operator is read out in accessible mode.
+fn operator_word(op: char) -> String {
+    match op {
+        '+' => "plus".to_string(),
+        '-' => "minus".to_string(),
+        '*' => "times".to_string(),
+        '/' => "divided by".to_string(),
+        '\\' => "floor divided by".to_string(),
+        '^' => "to the power of".to_string(),
+        '%' => "mod".to_string(),
+        other => other.to_string(),
+    }
+}
+
+/// Reads out `expr`, in spoken brackets if it binds looser than `min`.
+/// "15 percent" needs none, as said aloud it cannot be mistaken for `mod`.
+fn spoken_operand(expr: &Expr, min: u8) -> String {
+    if binding(expr) < min && !matches!(expr, Expr::Unary('%', _)) {
+        format!("open bracket {} close bracket", expr.spoken())
+    } else {
+        expr.spoken()
+    }
+}
+
+/// A number as words for a screen reader: `-3` is "minus 3".
+pub fn spoken_number(text: &str) -> String {
+    match text.strip_prefix('-') {
+        Some(rest) => format!("minus {}", rest),
+        None => text.to_string(),
+    }
+}
+
+impl Expr {
+    /// The expression with its operators spelled out, as in "5 plus 3",
+    /// for `--accessible`.
+    pub fn spoken(&self) -> String {
+        match self {
+            Expr::Literal(Value::Number(n)) => spoken_number(&n.to_string()),
+            Expr::Literal(_) | Expr::Name(_) => self.to_string(),
+            Expr::Unary('-', operand) => {
+                format!("minus {}", spoken_operand(operand, precedence('~')))
+            }
+            Expr::Unary('%', operand) => {
+                format!("{} percent", spoken_operand(operand, u8::MAX))
+            }
+            Expr::Unary('°', operand) => format!("{} degrees", operand.spoken()),
+            Expr::Unary('s', operand) => {
+                format!(
+                    "square root of {}",
+                    spoken_operand(operand, precedence('s'))
+                )
+            }
+            Expr::Unary(op, operand) => {
+                format!("{} {}", spoken_operand(operand, precedence(*op)), op)
+            }
+            Expr::Binary(op, left, right) => {
+                let level = precedence(*op);
+                format!(
+                    "{} {} {}",
+                    spoken_operand(left, level),
+                    operator_word(*op),
+                    spoken_operand(right, level + 1)
+                )
+            }
+            Expr::Call(name, args) => {
+                // A list literal reads as "list of 1, 2, 3".
+                let args: Vec<String> = args.iter().map(Expr::spoken).collect();
+                format!("{} of {}", name, args.join(", "))
+            }
+        }
+    }
+}
+
+/// `x + p%` and `x - p%` as on a desk calculator: `100 + 10%` is 110, not
+/// 100.1. Gives the operator, `x` and `p`.
+pub fn percent_change(expr: &Expr) -> Option<(char, &Expr, &Expr)> {
+    match expr {
+        Expr::Binary(op @ ('+' | '-'), base, right) => match right.as_ref() {
+            Expr::Unary('%', rate) => Some((*op, base, rate)),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+pub fn eval(expr: &Expr, env: &mut Env) -> Result<Value, String> {
+    if let Some((op, base, rate)) = percent_change(expr) {
+        let base = eval(base, env)?;
+        let share = apply_values(base.clone(), '*', apply_unary('%', eval(rate, env)?)?)?;
+        return apply_values(base, op, share);
+    }
+    match expr {
+        Expr::Literal(value) => Ok(value.clone()),
+        Expr::Name(name) if name == "MR" => Ok(Value::Number(env.memory)),
+        Expr::Name(name) => env
+            .variables
+            .get(name)
+            .cloned()
+            .or_else(|| constant(name))
+            .ok_or_else(|| format!("Unknown name: {}", name)),
+        // A DMS literal, as an angle in the current unit.
+        Expr::Unary('°', operand) => match eval(operand, env)? {
+            Value::Number(degrees) => Ok(Value::Number(env.settings.degrees_to_angle(degrees))),
+            other => Err(format!("° expects a number, got {}", other.kind())),
+        },
+        Expr::Unary(op, operand) => apply_unary(*op, eval(operand, env)?),
+        Expr::Binary(op, left, right) => {
+            let left = eval(left, env)?;
+            apply_values(left, *op, eval(right, env)?)
+        }
+        Expr::Call(name, args) if calculus::takes_expression(name) => {
+            calculus::call(name, args, env)
+        }
+        Expr::Call(name, args) => {
+            let values = args
+                .iter()
+                .map(|arg| match arg {
+                    // `unroman(MCMXCIX)`: a name that is not a variable is
+                    // the numeral itself.
+                    Expr::Name(numeral)
+                        if name == "unroman" && !env.variables.contains_key(numeral) =>
+                    {
+                        Ok(Value::Text(numeral.clone()))
+                    }
+                    _ => eval(arg, env),
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            call_function(name, values, env)
+        }
+    }
+}
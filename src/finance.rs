@@ -0,0 +1,139 @@
+//! Time-value-of-money functions with spreadsheet conventions: money paid
+//! out is negative, money received is positive, and `due` is 0 for payments
+//! at the end of each period or 1 for payments at the start.
+
+fn growth(rate: f64, periods: f64) -> f64 {
+    (1.0 + rate).powf(periods)
+}
+
+/// Future value after `periods` payments of `payment`, starting from `present`.
+pub fn fv(rate: f64, periods: f64, payment: f64, present: f64, due: f64) -> f64 {
+    if rate == 0.0 {
+        return -(present + payment * periods);
+    }
+    let g = growth(rate, periods);
+    -(present * g + payment * (1.0 + rate * due) * (g - 1.0) / rate)
+}
+
+/// Present value of `periods` payments of `payment` plus a final `future` amount.
+pub fn pv(rate: f64, periods: f64, payment: f64, future: f64, due: f64) -> f64 {
+    if rate == 0.0 {
+        return -(future + payment * periods);
+    }
+    let g = growth(rate, periods);
+    -(future + payment * (1.0 + rate * due) * (g - 1.0) / rate) / g
+}
+
+/// Payment per period that turns `present` into `future` over `periods`.
+pub fn pmt(rate: f64, periods: f64, present: f64, future: f64, due: f64) -> Result<f64, String> {
+    if periods == 0.0 {
+        return Err("pmt() needs at least one period".to_string());
+    }
+    if rate == 0.0 {
+        return Ok(-(present + future) / periods);
+    }
+    let g = growth(rate, periods);
+    Ok(-(future + present * g) * rate / ((1.0 + rate * due) * (g - 1.0)))
+}
+
+/// Number of periods needed to turn `present` into `future` with `payment`.
+pub fn nper(rate: f64, payment: f64, present: f64, future: f64, due: f64) -> Result<f64, String> {
+    if rate == 0.0 {
+        if payment == 0.0 {
+            return Err("nper() needs a non-zero payment when the rate is 0".to_string());
+        }
+        return Ok(-(present + future) / payment);
+    }
+    let adjusted = payment * (1.0 + rate * due);
+    let ratio = (adjusted - future * rate) / (adjusted + present * rate);
+    if ratio <= 0.0 {
+        return Err("The payment never reaches the target value".to_string());
+    }
+    Ok(ratio.ln() / (1.0 + rate).ln())
+}
+
+/// Net present value of cash flows at the end of periods 1, 2, ...
+pub fn npv(rate: f64, flows: &[f64]) -> f64 {
+    flows
+        .iter()
+        .enumerate()
+        .map(|(i, flow)| flow / growth(rate, i as f64 + 1.0))
+        .sum()
+}
+
+/// Internal rate of return of cash flows at periods 0, 1, 2, ...
+pub fn irr(flows: &[f64]) -> Result<f64, String> {
+    if !flows.iter().any(|&f| f > 0.0) || !flows.iter().any(|&f| f < 0.0) {
+        return Err("irr() needs at least one positive and one negative cash flow".to_string());
+    }
+    let value = |rate: f64| flows[0] + npv(rate, &flows[1..]);
+
+    // Newton's method from a typical guess, then bisection as a fallback.
+    let mut rate = 0.1;
+    for _ in 0..50 {
+        let f = value(rate);
+        let slope = (value(rate + 1e-6) - f) / 1e-6;
+        if slope == 0.0 || !slope.is_finite() {
+            break;
+        }
+        let next = rate - f / slope;
+        if !next.is_finite() || next <= -1.0 {
+            break;
+        }
+        if (next - rate).abs() < 1e-12 {
+            return Ok(next);
+        }
+        rate = next;
+    }
+
+    let (mut low, mut high) = (-0.9999, 10.0);
+    if value(low).signum() == value(high).signum() {
+        return Err("irr() did not converge".to_string());
+    }
+    for _ in 0..200 {
+        let mid = (low + high) / 2.0;
+        if value(mid).signum() == value(low).signum() {
+            low = mid;
+        } else {
+            high = mid;
+        }
+    }
+    Ok((low + high) / 2.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-2
+    }
+
+    #[test]
+    fn test_pmt_mortgage() {
+        let payment = pmt(0.05 / 12.0, 360.0, 250_000.0, 0.0, 0.0).unwrap();
+        assert!(close(payment, -1342.05));
+        assert!(close(pmt(0.0, 10.0, 1000.0, 0.0, 0.0).unwrap(), -100.0));
+    }
+
+    #[test]
+    fn test_fv_and_pv() {
+        assert!(close(fv(0.06 / 12.0, 10.0, -200.0, -500.0, 1.0), 2581.40));
+        assert!(close(pv(0.08 / 12.0, 240.0, 500.0, 0.0, 0.0), -59777.15));
+    }
+
+    #[test]
+    fn test_nper() {
+        let periods = nper(0.12 / 12.0, -100.0, -1000.0, 10000.0, 1.0).unwrap();
+        assert!(close(periods, 59.67));
+        assert!(nper(0.01, -5.0, 1000.0, 0.0, 0.0).is_err());
+    }
+
+    #[test]
+    fn test_npv_and_irr() {
+        let flows = [-10000.0, 3000.0, 4200.0, 6800.0];
+        assert!(close(npv(0.1, &flows[1..]) + flows[0], 1307.29));
+        assert!((irr(&flows).unwrap() - 0.1634).abs() < 1e-4);
+        assert!(irr(&[100.0, 200.0]).is_err());
+    }
+}
@@ -0,0 +1,74 @@
+//! Exact views of a double, for the `full` command.
+
+/// Splits a finite, non-zero `x` into an odd mantissa and an exponent with
+/// |x| = mantissa * 2^exponent.
+fn decompose(x: f64) -> (u64, i32) {
+    let bits = x.to_bits();
+    let raw_exponent = ((bits >> 52) & 0x7ff) as i32;
+    let fraction = bits & ((1 << 52) - 1);
+    let (mut mantissa, mut exponent) = match raw_exponent {
+        0 => (fraction, -1074),
+        _ => (fraction | 1 << 52, raw_exponent - 1075),
+    };
+    let zeros = mantissa.trailing_zeros();
+    mantissa >>= zeros;
+    exponent += zeros as i32;
+    (mantissa, exponent)
+}
+
+/// Every digit of the value the double holds, e.g. 0.1 is really
+/// 0.1000000000000000055511151231257827021181583404541015625.
+pub fn exact_decimal(x: f64) -> String {
+    if !x.is_finite() || x == 0.0 {
+        return x.to_string();
+    }
+    // A binary fraction with n bits after the point has exactly n decimals.
+    let (_, exponent) = decompose(x);
+    format!("{:.*}", (-exponent).max(0) as usize, x)
+}
+
+/// The value in C's `%a` notation, e.g. 0.1 is 0x1.999999999999ap-4.
+pub fn hex_float(x: f64) -> String {
+    if !x.is_finite() {
+        return x.to_string();
+    }
+    let sign = if x.is_sign_negative() { "-" } else { "" };
+    let bits = x.to_bits();
+    let raw_exponent = ((bits >> 52) & 0x7ff) as i32;
+    let fraction = bits & ((1 << 52) - 1);
+    let (lead, exponent) = match (raw_exponent, fraction) {
+        (0, 0) => (0, 0),
+        (0, _) => (0, -1022),
+        _ => (1, raw_exponent - 1023),
+    };
+    let digits = format!("{:013x}", fraction);
+    let digits = digits.trim_end_matches('0');
+    let point = if digits.is_empty() { "" } else { "." };
+    format!("{}0x{}{}{}p{:+}", sign, lead, point, digits, exponent)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exact_decimal() {
+        assert_eq!(
+            exact_decimal(0.1),
+            "0.1000000000000000055511151231257827021181583404541015625"
+        );
+        assert_eq!(exact_decimal(0.5), "0.5");
+        assert_eq!(exact_decimal(-8.0), "-8");
+        assert_eq!(exact_decimal(1e23), "99999999999999991611392");
+        assert_eq!(exact_decimal(0.0), "0");
+    }
+
+    #[test]
+    fn test_hex_float() {
+        assert_eq!(hex_float(0.1), "0x1.999999999999ap-4");
+        assert_eq!(hex_float(1.0), "0x1p+0");
+        assert_eq!(hex_float(-2.5), "-0x1.4p+1");
+        assert_eq!(hex_float(0.0), "0x0p+0");
+        assert_eq!(hex_float(f64::from_bits(1)), "0x0.0000000000001p-1022");
+    }
+}
@@ -0,0 +1,113 @@
+//! Fractions written the way people do, mixed numbers like `1 1/2`, and
+//! continued fractions for `cfrac`.
+
+fn count_digits(chars: &[char], start: usize) -> usize {
+    chars[start.min(chars.len())..]
+        .iter()
+        .take_while(|c| c.is_ascii_digit())
+        .count()
+}
+
+/// Length of a mixed number (`whole numerator/denominator`) at the start of
+/// `chars`, if there is one.
+pub fn scan_mixed(chars: &[char]) -> Option<usize> {
+    let whole = count_digits(chars, 0);
+    let spaces = chars[whole..].iter().take_while(|c| **c == ' ').count();
+    let numerator = count_digits(chars, whole + spaces);
+    if whole == 0 || spaces == 0 || numerator == 0 {
+        return None;
+    }
+    let slash = whole + spaces + numerator;
+    if chars.get(slash) != Some(&'/') {
+        return None;
+    }
+    let denominator = count_digits(chars, slash + 1);
+    let end = slash + 1 + denominator;
+    let zero = chars[slash + 1..end].iter().all(|&c| c == '0');
+    let more = chars
+        .get(end)
+        .is_some_and(|c| c.is_alphanumeric() || *c == '.');
+    (denominator > 0 && !zero && !more).then_some(end)
+}
+
+/// The value of a mixed number, which may have a leading minus.
+pub fn parse_mixed(text: &str) -> Option<f64> {
+    let (sign, body) = match text.strip_prefix('-') {
+        Some(rest) => (-1.0, rest),
+        None => (1.0, text),
+    };
+    let chars: Vec<char> = body.chars().collect();
+    if scan_mixed(&chars)? != chars.len() {
+        return None;
+    }
+    let (whole, fraction) = body.split_once(' ')?;
+    let (numerator, denominator) = fraction.trim_start().split_once('/')?;
+    let whole: f64 = whole.parse().ok()?;
+    let numerator: f64 = numerator.parse().ok()?;
+    let denominator: f64 = denominator.parse().ok()?;
+    Some(sign * (whole + numerator / denominator))
+}
+
+/// Up to `n` terms of the continued fraction of `x`, with the convergent
+/// `p/q` after each term. Stops early once a convergent is exactly `x`.
+pub fn continued_fraction(x: f64, n: usize) -> Vec<(i64, i128, i128)> {
+    let mut terms = vec![];
+    let (mut p, mut q): ((i128, i128), (i128, i128)) = ((1, 0), (0, 1));
+    let mut rest = x;
+    while terms.len() < n && rest.is_finite() && rest.abs() < 1e15 {
+        // Rounding error can leave 1.9999999999999996 where 2 was meant.
+        let a = match rest.round() {
+            near if (rest - near).abs() < 1e-9 => near,
+            _ => rest.floor(),
+        };
+        let Some(next_p) = (a as i128)
+            .checked_mul(p.0)
+            .and_then(|v| v.checked_add(p.1))
+        else {
+            break;
+        };
+        let Some(next_q) = (a as i128)
+            .checked_mul(q.0)
+            .and_then(|v| v.checked_add(q.1))
+        else {
+            break;
+        };
+        (p, q) = ((next_p, p.0), (next_q, q.0));
+        terms.push((a as i64, next_p, next_q));
+        if next_p as f64 / next_q as f64 == x || rest == a {
+            break;
+        }
+        rest = 1.0 / (rest - a);
+    }
+    terms
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_continued_fraction() {
+        let pi = continued_fraction(std::f64::consts::PI, 5);
+        let terms: Vec<i64> = pi.iter().map(|t| t.0).collect();
+        assert_eq!(terms, [3, 7, 15, 1, 292]);
+        assert_eq!((pi[3].1, pi[3].2), (355, 113));
+        // 0.1 is not exactly 1/10, but 1/10 is the double nearest to it.
+        let tenth = continued_fraction(0.1, 20);
+        assert_eq!(tenth.last().map(|t| (t.1, t.2)), Some((1, 10)));
+        assert_eq!(continued_fraction(-1.5, 10), [(-2, -2, 1), (2, -3, 2)]);
+        assert_eq!(continued_fraction(4.0, 10), [(4, 4, 1)]);
+    }
+
+    #[test]
+    fn test_mixed_numbers() {
+        let chars: Vec<char> = "1 1/2 + 3".chars().collect();
+        assert_eq!(scan_mixed(&chars), Some(5));
+        assert_eq!(parse_mixed("1 1/2"), Some(1.5));
+        assert_eq!(parse_mixed("-2 3/4"), Some(-2.75));
+        assert_eq!(parse_mixed("12  5/8"), Some(12.625));
+        for text in ["1/2", "1 1/0", "1 1/2.5", "1 + 1/2", "1 1/2x"] {
+            assert_eq!(parse_mixed(text), None, "{}", text);
+        }
+    }
+}
@@ -0,0 +1,790 @@
+use crate::angle;
+use crate::bigint::BigUint;
+use crate::bill;
+use crate::bits;
+use crate::calculus::tidy;
+use crate::distributions;
+use crate::divisors;
+use crate::duration::{format_duration, parse_duration};
+use crate::env::Env;
+use crate::finance;
+use crate::fraction;
+use crate::matrix::Matrix;
+use crate::network;
+use crate::pace;
+use crate::polynomial;
+use crate::radix;
+use crate::roman;
+use crate::sequences;
+use crate::stats;
+use crate::timestamp;
+use crate::value::Value;
+use crate::vat;
+use crate::vector;
+
+fn expect_args(name: &str, args: &[Value], count: usize) -> Result<(), String> {
+    if args.len() == count {
+        Ok(())
+    } else {
+        Err(format!(
+            "{}() takes {} argument(s), got {}",
+            name,
+            count,
+            args.len()
+        ))
+    }
+}
+
+fn expect_arg_range(name: &str, args: &[Value], min: usize, max: usize) -> Result<(), String> {
+    if (min..=max).contains(&args.len()) {
+        Ok(())
+    } else if max == usize::MAX {
+        Err(format!(
+            "{}() takes at least {} arguments, got {}",
+            name,
+            min,
+            args.len()
+        ))
+    } else {
+        Err(format!(
+            "{}() takes {} to {} arguments, got {}",
+            name,
+            min,
+            max,
+            args.len()
+        ))
+    }
+}
+
+fn number_arg(name: &str, value: &Value) -> Result<f64, String> {
+    match value {
+        Value::Number(n) => Ok(*n),
+        Value::Big(n) => Ok(n.to_f64()),
+        other => Err(format!("{}() expects a number, got {}", name, other.kind())),
+    }
+}
+
+fn integer_arg(name: &str, value: &Value) -> Result<i64, String> {
+    let n = number_arg(name, value)?;
+    if n.fract() == 0.0 && n.abs() < 9.0e15 {
+        Ok(n as i64)
+    } else {
+        Err(format!("{}() expects an integer, got {}", name, n))
+    }
+}
+
+/// All arguments as numbers, with unset optional trailing ones as 0.
+fn number_args(name: &str, args: &[Value], len: usize) -> Result<Vec<f64>, String> {
+    let mut numbers = args
+        .iter()
+        .map(|arg| number_arg(name, arg))
+        .collect::<Result<Vec<_>, _>>()?;
+    numbers.resize(len.max(numbers.len()), 0.0);
+    Ok(numbers)
+}
+
+/// Numbers of a variadic call like `mean(3, 7, 8, 12)`, with list
+/// arguments such as `mean(data)` spread in place.
+fn number_list(name: &str, args: &[Value]) -> Result<Vec<f64>, String> {
+    let mut numbers = Vec::new();
+    for arg in args {
+        match arg {
+            Value::List(items) => numbers.extend(items),
+            other => numbers.push(number_arg(name, other)?),
+        }
+    }
+    Ok(numbers)
+}
+
+fn vector_arg<'a>(name: &str, value: &'a Value) -> Result<&'a [f64], String> {
+    match value {
+        Value::List(items) => Ok(items),
+        other => Err(format!("{}() expects a vector, got {}", name, other.kind())),
+    }
+}
+
+/// An exact integer result: a number while it fits in a float without
+/// rounding, otherwise a `Big` that keeps every digit.
+fn exact(n: BigUint) -> Value {
+    match n.to_u64() {
+        Some(small) if small <= 1 << 53 => Value::Number(small as f64),
+        _ => Value::Big(n),
+    }
+}
+
+/// An integer argument to the bit functions cut to `width` bits. A `Big`
+/// (such as the result of `bitset(0, 63)`) is taken exactly.
+fn word_arg(name: &str, value: &Value, width: u32) -> Result<u64, String> {
+    match value {
+        Value::Big(n) => n
+            .to_u64()
+            .map(|n| bits::to_word(n as i64, width))
+            .ok_or_else(|| format!("{}() needs a value that fits in 64 bits", name)),
+        other => Ok(bits::to_word(integer_arg(name, other)?, width)),
+    }
+}
+
+fn matrix_arg<'a>(name: &str, value: &'a Value) -> Result<&'a Matrix, String> {
+    match value {
+        Value::Matrix(matrix) => Ok(matrix),
+        other => Err(format!("{}() expects a matrix, got {}", name, other.kind())),
+    }
+}
+
+fn network_arg(name: &str, value: &Value) -> Result<(u32, u8), String> {
+    match value {
+        Value::Network(address, prefix) => Ok((*address, *prefix)),
+        other => Err(format!(
+            "{}() expects a network such as /24 or 10.0.0.0/8, got {}",
+            name,
+            other.kind()
+        )),
+    }
+}
+
+/// A time in seconds, given as a duration (`3:45:00`, `25min`) or as text
+/// in the same form (`"3:45:00"`).
+fn time_arg(name: &str, value: &Value) -> Result<f64, String> {
+    match value {
+        Value::Duration(seconds) => Ok(*seconds),
+        Value::Text(text) => parse_duration(text.trim())
+            .ok_or_else(|| format!("{}() cannot read \"{}\" as a time", name, text)),
+        other => Err(format!(
+            "{}() expects a time such as 3:45:00, got {}",
+            name,
+            other.kind()
+        )),
+    }
+}
+
+fn text_arg<'a>(name: &str, value: &'a Value) -> Result<&'a str, String> {
+    match value {
+        Value::Text(text) => Ok(text),
+        other => Err(format!("{}() expects text, got {}", name, other.kind())),
+    }
+}
+
+/// Built-in constants, used for names that are not variables.
+pub fn constant(name: &str) -> Option<Value> {
+    match name {
+        "pi" => Some(Value::Number(std::f64::consts::PI)),
+        "e" => Some(Value::Number(std::f64::consts::E)),
+        _ => None,
+    }
+}
+
+/// Evaluates a built-in function call on already evaluated arguments.
+pub fn call_function(name: &str, args: Vec<Value>, env: &mut Env) -> Result<Value, String> {
+    match name {
+        "sin" | "cos" | "tan" | "asin" | "acos" | "atan" | "exp" | "ln" | "log" | "sqrt"
+        | "abs" | "floor" | "ceil" | "round" => {
+            expect_args(name, &args, 1)?;
+            let x = number_arg(name, &args[0])?;
+            let angles = &env.settings;
+            let y = match name {
+                "sin" => angles.angle_to_radians(x).sin(),
+                "cos" => angles.angle_to_radians(x).cos(),
+                "tan" => angles.angle_to_radians(x).tan(),
+                "asin" => angles.radians_to_angle(x.asin()),
+                "acos" => angles.radians_to_angle(x.acos()),
+                "atan" => angles.radians_to_angle(x.atan()),
+                "exp" => x.exp(),
+                "ln" if x > 0.0 => x.ln(),
+                "log" if x > 0.0 => x.log10(),
+                "sqrt" => x.sqrt(),
+                "abs" => x.abs(),
+                "floor" => x.floor(),
+                "ceil" => x.ceil(),
+                "round" => x.round(),
+                _ => f64::NAN,
+            };
+            if y.is_nan() {
+                Err(format!("{}() is not defined for {}", name, x))
+            } else {
+                Ok(Value::Number(y))
+            }
+        }
+        "dms" => {
+            expect_args(name, &args, 1)?;
+            let degrees = env.settings.angle_to_degrees(number_arg(name, &args[0])?);
+            Ok(Value::Text(angle::format_dms(degrees)))
+        }
+        "now" => {
+            expect_args(name, &args, 0)?;
+            Ok(Value::Time(timestamp::now()))
+        }
+        "epoch" => {
+            expect_args(name, &args, 1)?;
+            match args[0] {
+                Value::Time(t) => Ok(Value::Number(t)),
+                ref other => Err(format!("epoch() expects a date, got {}", other.kind())),
+            }
+        }
+        "fromepoch" => {
+            expect_args(name, &args, 1)?;
+            Ok(Value::Time(number_arg(name, &args[0])?))
+        }
+        "roman" => {
+            expect_args(name, &args, 1)?;
+            let n = integer_arg(name, &args[0])?;
+            u32::try_from(n)
+                .ok()
+                .and_then(roman::to_roman)
+                .map(Value::Text)
+                .ok_or_else(|| format!("roman() needs a number from 1 to 3999, got {}", n))
+        }
+        "unroman" => {
+            expect_args(name, &args, 1)?;
+            let text = text_arg(name, &args[0])?;
+            roman::from_roman(text)
+                .map(|n| Value::Number(n as f64))
+                .ok_or_else(|| format!("Invalid Roman numeral: {}", text))
+        }
+        "tobase" => {
+            expect_args(name, &args, 2)?;
+            let x = number_arg(name, &args[0])?;
+            let base = radix::check_base(integer_arg(name, &args[1])?)?;
+            if x.fract() == 0.0 {
+                let n = integer_arg(name, &args[0])?;
+                Ok(Value::Text(radix::to_base(n, base)))
+            } else if x.is_finite() {
+                radix::fraction_to_base(x, base).map(Value::Text)
+            } else {
+                Err(format!("tobase() cannot convert {}", x))
+            }
+        }
+        "frombase" => {
+            expect_args(name, &args, 2)?;
+            let text = text_arg(name, &args[0])?;
+            let base = radix::check_base(integer_arg(name, &args[1])?)?;
+            radix::from_base(text, base)
+                .map(|n| Value::Number(n as f64))
+                .ok_or_else(|| format!("\"{}\" is not a number in base {}", text, base))
+        }
+        "popcount" => {
+            expect_args(name, &args, 1)?;
+            let width = env.settings.word_size();
+            let word = word_arg(name, &args[0], width)?;
+            Ok(Value::Number(word.count_ones() as f64))
+        }
+        "bitget" | "bitset" | "bitclear" => {
+            expect_args(name, &args, 2)?;
+            let width = env.settings.word_size();
+            let word = word_arg(name, &args[0], width)?;
+            let bit = bits::check_bit(integer_arg(name, &args[1])?, width)?;
+            let result = match name {
+                "bitget" => word >> bit & 1,
+                "bitset" => word | 1 << bit,
+                _ => word & !(1 << bit),
+            };
+            Ok(exact(BigUint::from_u64(result)))
+        }
+        "rotl" | "rotr" => {
+            expect_arg_range(name, &args, 2, 3)?;
+            let width = match args.get(2) {
+                Some(width) => bits::check_width(integer_arg(name, width)?)?,
+                None => env.settings.word_size(),
+            };
+            let word = word_arg(name, &args[0], width)?;
+            let by = integer_arg(name, &args[1])?;
+            let by = u32::try_from(by).map_err(|_| format!("{}() needs a count >= 0", name))?;
+            let result = if name == "rotl" {
+                bits::rotl(word, by, width)
+            } else {
+                bits::rotr(word, by, width)
+            };
+            Ok(exact(BigUint::from_u64(result)))
+        }
+        "tip" => {
+            expect_arg_range(name, &args, 2, 3)?;
+            let bill = number_arg(name, &args[0])?;
+            let rate = number_arg(name, &args[1])?;
+            let people = match args.get(2) {
+                Some(people) => number_arg(name, people)?,
+                None => 1.0,
+            };
+            let split = bill::split_bill(bill, rate, people)?;
+            Ok(Value::Text(split.parts().join(", ")))
+        }
+        "fv" | "pv" | "pmt" | "nper" => {
+            expect_arg_range(name, &args, 3, 5)?;
+            let n = number_args(name, &args, 5)?;
+            let result = match name {
+                "fv" => finance::fv(n[0], n[1], n[2], n[3], n[4]),
+                "pv" => finance::pv(n[0], n[1], n[2], n[3], n[4]),
+                "pmt" => finance::pmt(n[0], n[1], n[2], n[3], n[4])?,
+                _ => finance::nper(n[0], n[1], n[2], n[3], n[4])?,
+            };
+            Ok(Value::Number(result))
+        }
+        "npv" => {
+            expect_arg_range(name, &args, 2, usize::MAX)?;
+            let n = number_args(name, &args, 0)?;
+            Ok(Value::Number(finance::npv(n[0], &n[1..])))
+        }
+        "irr" => {
+            expect_arg_range(name, &args, 2, usize::MAX)?;
+            let n = number_args(name, &args, 0)?;
+            Ok(Value::Number(finance::irr(&n)?))
+        }
+        "vat_add" | "vat_remove" => {
+            expect_arg_range(name, &args, 1, 2)?;
+            let amount = number_arg(name, &args[0])?;
+            let rate = match args.get(1) {
+                Some(rate) => vat::check_rate(number_arg(name, rate)?)?,
+                None => env
+                    .settings
+                    .vat_rate
+                    .ok_or("No default VAT rate, set one with 'set vat <rate>'")?,
+            };
+            let result = if name == "vat_add" {
+                vat::add_vat(amount, rate)
+            } else {
+                vat::remove_vat(amount, rate)
+            };
+            Ok(Value::Number(result))
+        }
+        "pctchange" => {
+            expect_args(name, &args, 2)?;
+            let old = number_arg(name, &args[0])?;
+            let new = number_arg(name, &args[1])?;
+            if old == 0.0 {
+                return Err("pctchange() cannot start from 0".to_string());
+            }
+            Ok(Value::Number((new - old) / old.abs() * 100.0))
+        }
+        "pctof" => {
+            expect_args(name, &args, 2)?;
+            let part = number_arg(name, &args[0])?;
+            let whole = number_arg(name, &args[1])?;
+            if whole == 0.0 {
+                return Err("pctof() needs a non-zero whole".to_string());
+            }
+            Ok(Value::Number(part / whole * 100.0))
+        }
+        "mean" | "median" | "variance" | "pvariance" | "stddev" | "pstddev" | "min" | "max"
+        | "sum" => {
+            let values = number_list(name, &args)?;
+            let statistic = match name {
+                "mean" => stats::mean,
+                "median" => stats::median,
+                "variance" => stats::variance,
+                "pvariance" => stats::pvariance,
+                "stddev" => stats::stddev,
+                "pstddev" => stats::pstddev,
+                "min" => stats::min,
+                "max" => stats::max,
+                _ => stats::sum,
+            };
+            Ok(Value::Number(statistic(&values)?))
+        }
+        "rand" => {
+            expect_args(name, &args, 0)?;
+            Ok(Value::Number(env.rng.uniform()))
+        }
+        "randint" => {
+            expect_args(name, &args, 2)?;
+            let low = integer_arg(name, &args[0])?;
+            let high = integer_arg(name, &args[1])?;
+            if low > high {
+                return Err(format!(
+                    "randint() needs low <= high, got {} and {}",
+                    low, high
+                ));
+            }
+            Ok(Value::Number(env.rng.int_between(low, high) as f64))
+        }
+        "randn" => {
+            expect_arg_range(name, &args, 0, 2)?;
+            let n = number_args(name, &args, 2)?;
+            let sigma = if args.len() < 2 { 1.0 } else { n[1] };
+            if sigma < 0.0 {
+                return Err("randn() needs a non-negative sigma".to_string());
+            }
+            Ok(Value::Number(env.rng.normal(n[0], sigma)))
+        }
+        "normcdf" | "norminv" => {
+            expect_arg_range(name, &args, 1, 3)?;
+            let n = number_args(name, &args, 3)?;
+            let sigma = if args.len() < 3 { 1.0 } else { n[2] };
+            let result = if name == "normcdf" {
+                distributions::normcdf(n[0], n[1], sigma)?
+            } else {
+                distributions::norminv(n[0], n[1], sigma)?
+            };
+            Ok(Value::Number(result))
+        }
+        "binompdf" => {
+            expect_args(name, &args, 3)?;
+            let n = number_args(name, &args, 3)?;
+            Ok(Value::Number(distributions::binompdf(n[0], n[1], n[2])?))
+        }
+        "poissonpdf" => {
+            expect_args(name, &args, 2)?;
+            let n = number_args(name, &args, 2)?;
+            Ok(Value::Number(distributions::poissonpdf(n[0], n[1])?))
+        }
+        "list" => {
+            // `[[1, 2], [3, 4]]`: a list of lists is a matrix.
+            if !args.is_empty() && args.iter().all(|arg| matches!(arg, Value::List(_))) {
+                let rows = args
+                    .into_iter()
+                    .map(|arg| match arg {
+                        Value::List(row) => row,
+                        _ => unreachable!(),
+                    })
+                    .collect();
+                return Matrix::from_rows(rows).map(Value::Matrix);
+            }
+            let items = args
+                .iter()
+                .map(|arg| number_arg(name, arg))
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(Value::List(items))
+        }
+        "dot" => {
+            expect_args(name, &args, 2)?;
+            let (a, b) = (vector_arg(name, &args[0])?, vector_arg(name, &args[1])?);
+            Ok(Value::Number(vector::dot(a, b)?))
+        }
+        "cross" => {
+            expect_args(name, &args, 2)?;
+            let (a, b) = (vector_arg(name, &args[0])?, vector_arg(name, &args[1])?);
+            Ok(Value::List(vector::cross(a, b)?))
+        }
+        "norm" => {
+            expect_args(name, &args, 1)?;
+            Ok(Value::Number(vector::norm(vector_arg(name, &args[0])?)))
+        }
+        "triangular" => {
+            expect_args(name, &args, 1)?;
+            let n = integer_arg(name, &args[0])?;
+            let n = u64::try_from(n).map_err(|_| format!("{}() needs n >= 0", name))?;
+            Ok(exact(sequences::triangular(n)?))
+        }
+        "fib" | "catalan" => {
+            expect_args(name, &args, 1)?;
+            let n = integer_arg(name, &args[0])?;
+            let n = u32::try_from(n).map_err(|_| format!("{}() needs n >= 0", name))?;
+            let value = match name {
+                "fib" => sequences::fib(n)?,
+                _ => sequences::catalan(n)?,
+            };
+            Ok(exact(value))
+        }
+        "divisors" => {
+            expect_args(name, &args, 1)?;
+            let n = integer_arg(name, &args[0])?;
+            let found = divisors::divisors(n)?;
+            Ok(Value::List(found.into_iter().map(|d| d as f64).collect()))
+        }
+        "sigma" | "totient" => {
+            expect_args(name, &args, 1)?;
+            let n = integer_arg(name, &args[0])?;
+            let value = match name {
+                "sigma" => divisors::sigma(n)?,
+                _ => divisors::totient(n)?,
+            };
+            Ok(Value::Number(value as f64))
+        }
+        "pace" => {
+            expect_args(name, &args, 2)?;
+            let distance = number_arg(name, &args[0])?;
+            let (per_km, per_mile) = pace::pace(distance, time_arg(name, &args[1])?)?;
+            // Paces are quoted to the second.
+            Ok(Value::Text(format!(
+                "{} per km, {} per mile",
+                format_duration(per_km.round()),
+                format_duration(per_mile.round())
+            )))
+        }
+        "speed" => {
+            expect_args(name, &args, 2)?;
+            let distance = number_arg(name, &args[0])?;
+            Ok(Value::Number(pace::speed(
+                distance,
+                time_arg(name, &args[1])?,
+            )?))
+        }
+        "l100km" | "mpg" => {
+            expect_args(name, &args, 1)?;
+            let value = number_arg(name, &args[0])?;
+            Ok(Value::Number(pace::fuel_economy(value)?))
+        }
+        "cfrac" => {
+            expect_args(name, &args, 2)?;
+            let x = number_arg(name, &args[0])?;
+            let n = integer_arg(name, &args[1])?;
+            if !(1..=50).contains(&n) {
+                return Err(format!("cfrac() shows 1 to 50 terms, got {}", n));
+            }
+            let terms = fraction::continued_fraction(x, n as usize);
+            if terms.is_empty() {
+                return Err(format!("cfrac() cannot expand {}", x));
+            }
+            let mut shown = format!("[{}", terms[0].0);
+            for (i, (a, _, _)) in terms.iter().enumerate().skip(1) {
+                shown.push_str(if i == 1 { "; " } else { ", " });
+                shown.push_str(&a.to_string());
+            }
+            let convergents: Vec<String> = terms
+                .iter()
+                .map(|&(_, p, q)| match q {
+                    1 => p.to_string(),
+                    _ => format!("{}/{}", p, q),
+                })
+                .collect();
+            Ok(Value::Text(format!(
+                "{}]  convergents {}",
+                shown,
+                convergents.join(", ")
+            )))
+        }
+        "divmod" => {
+            expect_args(name, &args, 2)?;
+            let n = number_args(name, &args, 2)?;
+            if n[1] == 0.0 {
+                return Err("divmod() cannot divide by zero".to_string());
+            }
+            // Floored like `//`, so the remainder takes the divisor's sign.
+            let quotient = (n[0] / n[1]).floor();
+            Ok(Value::List(vec![quotient, n[0] - quotient * n[1]]))
+        }
+        "range" => {
+            expect_arg_range(name, &args, 2, 3)?;
+            let n = number_args(name, &args, 3)?;
+            let step = if args.len() == 3 { n[2] } else { 1.0 };
+            if step == 0.0 || (n[1] - n[0]) * step < 0.0 {
+                return Err(format!(
+                    "range() cannot step from {} to {} by {}",
+                    n[0], n[1], step
+                ));
+            }
+            // A little slack so range(0, 1, 0.1) still ends at 1.
+            let count = ((n[1] - n[0]) / step + 1e-9).floor() + 1.0;
+            if count > 1e6 {
+                return Err("range() is limited to a million values".to_string());
+            }
+            let items = (0..count as usize)
+                .map(|i| tidy(n[0] + i as f64 * step))
+                .collect();
+            Ok(Value::List(items))
+        }
+        "hosts" => {
+            expect_args(name, &args, 1)?;
+            let (_, prefix) = network_arg(name, &args[0])?;
+            Ok(Value::Number(network::hosts(prefix) as f64))
+        }
+        "netmask" => {
+            expect_args(name, &args, 1)?;
+            let (_, prefix) = network_arg(name, &args[0])?;
+            Ok(Value::Address(network::netmask(prefix)))
+        }
+        "inrange" => {
+            expect_args(name, &args, 2)?;
+            let Value::Address(address) = args[0] else {
+                return Err(format!(
+                    "inrange() expects an address first, got {}",
+                    args[0].kind()
+                ));
+            };
+            let (net, prefix) = network_arg(name, &args[1])?;
+            let inside = network::contains(net, prefix, address);
+            Ok(Value::Number(if inside { 1.0 } else { 0.0 }))
+        }
+        "quadratic" | "cubic" => {
+            let degree = if name == "quadratic" { 2 } else { 3 };
+            expect_args(name, &args, degree + 1)?;
+            let coeffs = number_list(name, &args)?;
+            if coeffs[0] == 0.0 {
+                return Err(format!("{}() needs a non-zero first coefficient", name));
+            }
+            let show = |x: f64| env.settings.format_number(tidy(x));
+            let mut roots: Vec<String> = polynomial::real_roots(&coeffs)?
+                .into_iter()
+                .map(show)
+                .collect();
+            if let Some((re, im)) = polynomial::complex_pair(&coeffs) {
+                let im = match show(im).as_str() {
+                    "1" => "i".to_string(),
+                    im => format!("{}i", im),
+                };
+                roots.push(match show(re).as_str() {
+                    "0" | "-0" => format!("±{}", im),
+                    re => format!("{} ± {}", re, im),
+                });
+            }
+            Ok(Value::Text(format!(
+                "x = {}  (discriminant {})",
+                roots.join(", "),
+                show(polynomial::discriminant(&coeffs))
+            )))
+        }
+        "roots" => {
+            let coeffs = number_list(name, &args)?;
+            polynomial::real_roots(&coeffs).map(Value::List)
+        }
+        "det" => {
+            expect_args(name, &args, 1)?;
+            Ok(Value::Number(matrix_arg(name, &args[0])?.det()?))
+        }
+        "inv" => {
+            expect_args(name, &args, 1)?;
+            matrix_arg(name, &args[0])?.inverse().map(Value::Matrix)
+        }
+        "linsolve" => {
+            expect_args(name, &args, 2)?;
+            let a = matrix_arg(name, &args[0])?;
+            let b = match &args[1] {
+                Value::List(items) => items.clone(),
+                Value::Matrix(column) if column.cols == 1 => column.transpose().row(0).to_vec(),
+                other => {
+                    return Err(format!(
+                        "linsolve() expects a vector or a one-column matrix, got {}",
+                        other.kind()
+                    ));
+                }
+            };
+            a.solve(&b).map(Value::List)
+        }
+        "transpose" => {
+            expect_args(name, &args, 1)?;
+            Ok(Value::Matrix(matrix_arg(name, &args[0])?.transpose()))
+        }
+        "linreg" => {
+            expect_args(name, &args, 2)?;
+            let xs = number_list(name, &args[..1])?;
+            let ys = number_list(name, &args[1..])?;
+            let (slope, intercept, r2) = stats::linreg(&xs, &ys)?;
+            for (variable, value) in [("slope", slope), ("intercept", intercept), ("r2", r2)] {
+                env.define(variable, Value::Number(value), "linreg()");
+            }
+            Ok(Value::Text(format!(
+                "slope = {}, intercept = {}, r2 = {}",
+                slope, intercept, r2
+            )))
+        }
+        _ => Err(format!("Unknown function: {}", name)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn call(name: &str, args: Vec<Value>) -> Result<Value, String> {
+        call_function(name, args, &mut Env::default())
+    }
+
+    #[test]
+    fn test_epoch_round_trip() {
+        let time = call("fromepoch", vec![Value::Number(1714557600.0)]).unwrap();
+        assert_eq!(time.to_string(), "2024-05-01 10:00:00 UTC");
+        let back = call("epoch", vec![time]).unwrap();
+        assert_eq!(back, Value::Number(1714557600.0));
+    }
+
+    #[test]
+    fn test_divmod() {
+        let divmod = |a: f64, b: f64| call("divmod", vec![Value::Number(a), Value::Number(b)]);
+        assert_eq!(divmod(17.0, 5.0).unwrap(), Value::List(vec![3.0, 2.0]));
+        assert_eq!(divmod(-7.0, 2.0).unwrap(), Value::List(vec![-4.0, 1.0]));
+        assert_eq!(divmod(7.0, -2.0).unwrap(), Value::List(vec![-4.0, -1.0]));
+        assert!(divmod(1.0, 0.0).is_err());
+    }
+
+    #[test]
+    fn test_math_functions() {
+        let one = |name: &str, x: f64| call(name, vec![Value::Number(x)]);
+        assert_eq!(one("sin", 0.0).unwrap(), Value::Number(0.0));
+        assert_eq!(one("log", 1000.0).unwrap(), Value::Number(3.0));
+        assert_eq!(one("round", 2.5).unwrap(), Value::Number(3.0));
+        assert_eq!(one("abs", -4.0).unwrap(), Value::Number(4.0));
+        assert!(one("ln", 0.0).is_err());
+        assert!(one("sqrt", -1.0).is_err());
+        assert!(one("acos", 2.0).is_err());
+        assert_eq!(constant("pi"), Some(Value::Number(std::f64::consts::PI)));
+        assert_eq!(constant("tau"), None);
+    }
+
+    #[test]
+    fn test_function_errors() {
+        assert!(call("now", vec![Value::Number(1.0)]).is_err());
+        assert!(call("epoch", vec![Value::Number(1.0)]).is_err());
+        assert!(call("nosuch", vec![]).is_err());
+    }
+
+    #[test]
+    fn test_roman_functions() {
+        let numeral = call("roman", vec![Value::Number(2024.0)]).unwrap();
+        assert_eq!(numeral, Value::Text("MMXXIV".into()));
+        let back = call("unroman", vec![Value::Text("MCMXCIX".into())]).unwrap();
+        assert_eq!(back, Value::Number(1999.0));
+        assert!(call("roman", vec![Value::Number(2.5)]).is_err());
+        assert!(call("roman", vec![Value::Number(0.0)]).is_err());
+        assert!(call("unroman", vec![Value::Number(4.0)]).is_err());
+    }
+
+    #[test]
+    fn test_percent_functions() {
+        let change = call("pctchange", vec![Value::Number(80.0), Value::Number(95.0)]);
+        assert_eq!(change.unwrap(), Value::Number(18.75));
+        let drop = call(
+            "pctchange",
+            vec![Value::Number(-50.0), Value::Number(-75.0)],
+        );
+        assert_eq!(drop.unwrap(), Value::Number(-50.0));
+        let share = call("pctof", vec![Value::Number(30.0), Value::Number(120.0)]);
+        assert_eq!(share.unwrap(), Value::Number(25.0));
+        assert!(call("pctchange", vec![Value::Number(0.0), Value::Number(5.0)]).is_err());
+        assert!(call("pctof", vec![Value::Number(1.0), Value::Number(0.0)]).is_err());
+    }
+
+    #[test]
+    fn test_statistics_spread_lists() {
+        let args = vec![Value::List(vec![3.0, 7.0, 8.0]), Value::Number(12.0)];
+        assert_eq!(call("mean", args).unwrap(), Value::Number(7.5));
+        assert!(call("max", vec![Value::Text("x".into())]).is_err());
+    }
+
+    #[test]
+    fn test_linreg_stores_variables() {
+        let mut env = Env::default();
+        let xs = Value::List(vec![1.0, 2.0, 3.0]);
+        let ys = Value::List(vec![3.0, 5.0, 7.0]);
+        let summary = call_function("linreg", vec![xs, ys], &mut env).unwrap();
+        assert_eq!(summary.to_string(), "slope = 2, intercept = 1, r2 = 1");
+        assert_eq!(env.variables["slope"], Value::Number(2.0));
+        assert_eq!(env.variables["intercept"], Value::Number(1.0));
+        assert_eq!(env.variables["r2"], Value::Number(1.0));
+    }
+
+    #[test]
+    fn test_random_functions() {
+        let mut env = Env::default();
+        env.rng.seed(5);
+        let first = call_function("rand", vec![], &mut env).unwrap();
+        env.rng.seed(5);
+        assert_eq!(call_function("rand", vec![], &mut env).unwrap(), first);
+
+        let roll = vec![Value::Number(1.0), Value::Number(6.0)];
+        let Value::Number(die) = call_function("randint", roll, &mut env).unwrap() else {
+            panic!("randint() should return a number");
+        };
+        assert!((1.0..=6.0).contains(&die));
+        assert!(call("randint", vec![Value::Number(6.0), Value::Number(1.0)]).is_err());
+        assert!(call("randn", vec![Value::Number(0.0), Value::Number(-1.0)]).is_err());
+    }
+
+    #[test]
+    fn test_vat_default_rate() {
+        let mut env = Env::default();
+        let net = vec![Value::Number(100.0)];
+        assert!(call_function("vat_add", net.clone(), &mut env).is_err());
+        env.settings.set("vat", 0.2).unwrap();
+        assert_eq!(
+            call_function("vat_add", net, &mut env).unwrap(),
+            Value::Number(120.0)
+        );
+    }
+}
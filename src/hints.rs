@@ -0,0 +1,124 @@
+//! Fish-style hints: while a line is typed, the rest of the most recent
+//! earlier input that starts the same way is shown dimmed after the cursor.
+//! → takes it into the line without running it.
+
+use colored::Colorize;
+use rustyline::completion::Completer;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::validate::Validator;
+use rustyline::{Context, Helper};
+use std::borrow::Cow;
+
+/// Earlier inputs, oldest first: the expressions from `history.txt`, then
+/// the lines typed in this session as they were typed.
+#[derive(Debug, Default)]
+pub struct Hints {
+    inputs: Vec<String>,
+}
+
+impl Hints {
+    pub fn new(inputs: Vec<String>) -> Self {
+        Hints { inputs }
+    }
+
+    /// Records a typed line as the newest input.
+    pub fn add(&mut self, line: &str) {
+        if line.is_empty() {
+            return;
+        }
+        self.inputs.retain(|input| input != line);
+        self.inputs.push(line.to_string());
+    }
+}
+
+/// What follows `line` in the most recent earlier input that starts with
+/// it. Spaces do not have to match, so `2+` is completed from `2 + 3`.
+pub fn hint<'a>(line: &str, inputs: &'a [String]) -> Option<&'a str> {
+    if line.trim().is_empty() {
+        return None;
+    }
+    inputs
+        .iter()
+        .rev()
+        .filter_map(|input| rest_after(line, input))
+        .find(|rest| !rest.is_empty())
+}
+
+fn rest_after<'a>(line: &str, input: &'a str) -> Option<&'a str> {
+    let mut rest = input;
+    for c in line.chars() {
+        if let Some(after) = rest.strip_prefix(c) {
+            rest = after;
+        } else if let Some(after) = rest.trim_start_matches(' ').strip_prefix(c) {
+            rest = after;
+        } else if c != ' ' {
+            return None;
+        }
+    }
+    Some(rest)
+}
+
+impl Hinter for Hints {
+    type Hint = String;
+
+    fn hint(&self, line: &str, pos: usize, _ctx: &Context<'_>) -> Option<String> {
+        if pos < line.len() {
+            return None;
+        }
+        hint(line, &self.inputs).map(str::to_string)
+    }
+}
+
+impl Highlighter for Hints {
+    fn highlight_hint<'h>(&self, hint: &'h str) -> Cow<'h, str> {
+        Cow::Owned(hint.dimmed().to_string())
+    }
+}
+
+impl Completer for Hints {
+    type Candidate = String;
+}
+
+impl Validator for Hints {}
+
+impl Helper for Hints {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn inputs(lines: &[&str]) -> Vec<String> {
+        lines.iter().map(|line| line.to_string()).collect()
+    }
+
+    #[test]
+    fn test_hint_latest_match() {
+        let inputs = inputs(&["120 * 1.21", "sqrt(2)", "120 * 1.06"]);
+        assert_eq!(hint("120", &inputs), Some(" * 1.06"));
+        assert_eq!(hint("120 * 1.2", &inputs), Some("1"));
+        assert_eq!(hint("sq", &inputs), Some("rt(2)"));
+        assert_eq!(hint("cos", &inputs), None);
+        assert_eq!(hint("", &inputs), None);
+        // A line that is already complete has nothing to add.
+        assert_eq!(hint("sqrt(2)", &inputs), None);
+    }
+
+    #[test]
+    fn test_hint_ignores_spacing() {
+        let inputs = inputs(&["2 + 3 * 4", "2+3"]);
+        assert_eq!(hint("2+", &inputs), Some("3"));
+        assert_eq!(hint("2+3", &inputs), Some(" * 4"));
+        assert_eq!(hint("2 +", &inputs), Some("3"));
+        assert_eq!(hint("2 + 3 *", &inputs), Some(" 4"));
+    }
+
+    #[test]
+    fn test_add_keeps_newest_last() {
+        let mut hints = Hints::new(inputs(&["1 + 1", "2 * 2"]));
+        hints.add("1 + 1");
+        hints.add("");
+        assert_eq!(hint("1", &hints.inputs), Some(" + 1"));
+        assert_eq!(hints.inputs, ["2 * 2", "1 + 1"]);
+    }
+}
@@ -1,87 +1,418 @@
+mod amortize;
+mod angle;
+mod bigint;
+mod bill;
+mod bits;
+mod calculus;
+mod data;
+mod distributions;
+mod divisors;
+mod duration;
+mod env;
+mod explain;
+mod export;
+mod expr;
+mod finance;
+mod float;
+mod fraction;
+mod functions;
+mod hints;
+mod matrix;
+mod network;
+mod pace;
+mod plot;
+mod polynomial;
+mod radix;
+mod random;
+mod roman;
+mod rpn;
+mod sequences;
+mod settings;
+mod simplify;
+mod sparkline;
+mod stats;
+mod timestamp;
+mod transcript;
+mod value;
+mod vat;
+mod vector;
+mod words;
+
+use angle::{parse_dms, scan_dms};
 use colored::*;
 use core::fmt;
+use duration::{parse_duration, scan_duration};
+use env::Env;
+use expr::{Expr, eval, spoken_number};
+use fraction::{parse_mixed, scan_mixed};
+use hints::Hints;
+use network::{parse_address, parse_network, scan_network};
+use rustyline::Editor;
+use rustyline::error::ReadlineError;
+use rustyline::history::DefaultHistory;
+use settings::Settings;
 use std::{
     fs,
-    io::{self, Write},
+    io::{self, IsTerminal, Write},
 };
+use timestamp::{format_timestamp, parse_date, scan_date};
+use transcript::Transcript;
+use value::Value;
 
 struct HistoryEntry {
     expression: String,
-    result: f64,
+    result: Value,
+    /// Typed in `mode rpn`, so `expression` is reverse Polish.
+    rpn: bool,
+}
+
+impl HistoryEntry {
+    fn new(expression: String, result: Value) -> Self {
+        HistoryEntry {
+            expression,
+            result,
+            rpn: false,
+        }
+    }
+
+    fn new_rpn(expression: String, result: Value) -> Self {
+        HistoryEntry {
+            rpn: true,
+            ..HistoryEntry::new(expression, result)
+        }
+    }
+
+    fn tag(&self) -> &'static str {
+        if self.rpn { RPN_TAG } else { "" }
+    }
 }
 
 impl HistoryEntry {
-    fn new(expression: String, result: f64) -> Self {
-        HistoryEntry { expression, result }
+    /// The entry as listed in the REPL, following the decimals settings.
+    fn show(&self, settings: &Settings) -> String {
+        format!(
+            "{}{} = {}",
+            self.tag(),
+            self.expression,
+            self.result.show(settings)
+        )
     }
 }
 
 impl fmt::Display for HistoryEntry {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{} = {}", self.expression, self.result)
+        write!(f, "{}{} = {}", self.tag(), self.expression, self.result)
     }
 }
 
-fn save_history(history: &Vec<HistoryEntry>) -> Result<(), String> {
-    let mut file =
-        fs::File::create("history.txt").map_err(|e| format!("Cannot create file: {}", e))?;
+const HISTORY_FILE: &str = "history.txt";
+
+/// Format of the history file, given in its first line. Version 1 files
+/// have no header.
+const HISTORY_VERSION: u32 = 3;
+const HISTORY_HEADER: &str = "# cll-calculator history v";
 
+/// Marks history lines typed in `mode rpn` (from format version 3).
+const RPN_TAG: &str = "rpn: ";
+
+/// How many backups of the history file are kept.
+const HISTORY_BACKUPS: usize = 5;
+
+fn save_history(path: &str, history: &Vec<HistoryEntry>) -> Result<(), String> {
+    let mut text = format!("{}{}\n", HISTORY_HEADER, HISTORY_VERSION);
     for entry in history {
-        writeln!(file, "{}", entry).map_err(|e| format!("Write Error: {}", e))?;
+        text.push_str(&format!("{}\n", entry));
+    }
+    match fs::read_to_string(path) {
+        Ok(old) if old != text => {
+            let stamp: String = format_timestamp(timestamp::now())
+                .chars()
+                .filter(char::is_ascii_digit)
+                .collect();
+            backup_history(path, &stamp)?
+        }
+        _ => {}
     }
+    let mut file = fs::File::create(path).map_err(|e| format!("Cannot create file: {}", e))?;
+    file.write_all(text.as_bytes())
+        .map_err(|e| format!("Write Error: {}", e))?;
 
     Ok(())
 }
 
-fn load_history() -> Vec<HistoryEntry> {
-    match fs::read_to_string("history.txt") {
-        Ok(content) => content
-            .lines()
+/// Copies the history file to `<path>.<stamp>.bak` before it is overwritten,
+/// and removes all but the newest backups.
+fn backup_history(path: &str, stamp: &str) -> Result<(), String> {
+    let backup = format!("{}.{}.bak", path, stamp);
+    fs::copy(path, &backup).map_err(|e| format!("Cannot back up {}: {}", path, e))?;
+
+    let file = std::path::Path::new(path);
+    let dir = match file.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => std::path::Path::new("."),
+    };
+    let name = file.file_name().unwrap_or_default().to_string_lossy();
+    let mut backups: Vec<_> = fs::read_dir(dir)
+        .map_err(|e| format!("Cannot list {}: {}", dir.display(), e))?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|backup| {
+            let backup_name = backup.file_name().unwrap_or_default().to_string_lossy();
+            backup_name.starts_with(&format!("{}.", name)) && backup_name.ends_with(".bak")
+        })
+        .collect();
+    backups.sort();
+    for old in &backups[..backups.len().saturating_sub(HISTORY_BACKUPS)] {
+        fs::remove_file(old).ok();
+    }
+    Ok(())
+}
+
+/// Reads the history file, migrating older formats. Lines that cannot be
+/// read are counted rather than dropped silently; the file itself is backed
+/// up before it is next written.
+fn load_history(path: &str) -> Result<(Vec<HistoryEntry>, usize), String> {
+    let Ok(content) = fs::read_to_string(path) else {
+        return Ok((Vec::new(), 0));
+    };
+    let mut lines = content.lines().peekable();
+    let version = match lines
+        .peek()
+        .and_then(|line| line.strip_prefix(HISTORY_HEADER))
+    {
+        Some(version) => {
+            let version = version.trim().parse::<u32>().unwrap_or(u32::MAX);
+            lines.next();
+            version
+        }
+        None => 1,
+    };
+    let lines: Vec<&str> = lines.filter(|line| !line.trim().is_empty()).collect();
+    let entries = migrate_history(version, &lines)?;
+    let skipped = lines.len() - entries.len();
+    Ok((entries, skipped))
+}
+
+/// Turns the lines of a history file of the given version into entries.
+fn migrate_history(version: u32, lines: &[&str]) -> Result<Vec<HistoryEntry>, String> {
+    match version {
+        // Version 2 only added the header; version 3 tags RPN lines.
+        1..=3 => Ok(lines
+            .iter()
             .filter_map(|line| {
-                if let Some(pos) = line.find(" = ") {
-                    let expression = line[..pos].to_string();
-                    let result = line[pos + 3..].parse::<f64>().ok()?;
-                    Some(HistoryEntry::new(expression, result))
-                } else {
-                    None
-                }
+                let (expression, result) = line.split_once(" = ")?;
+                let result = Value::parse(result)?;
+                let rpn = match expression.strip_prefix(RPN_TAG) {
+                    Some(rpn) if version >= 3 => rpn,
+                    // Before the tag, an RPN line is told apart by its
+                    // final operator, which no algebraic line ends with.
+                    _ if version < 3 && ends_in_binary_operator(expression) => expression,
+                    _ => return Some(HistoryEntry::new(expression.to_string(), result)),
+                };
+                Some(HistoryEntry::new_rpn(rpn.to_string(), result))
             })
-            .collect(),
-        Err(_) => Vec::new(),
+            .collect()),
+        _ => Err(format!(
+            "The history file is from a newer version of the calculator \
+             (format v{}, this one reads up to v{})",
+            version, HISTORY_VERSION
+        )),
     }
 }
 
 fn precedence(op: char) -> u8 {
     match op {
+        '~' => 5,
         's' => 4,
         '^' => 3,
-        '*' | '/' | '%' => 2,
+        '*' | '/' | '\\' | '%' => 2,
         '+' | '-' => 1,
         _ => 0,
     }
 }
 
 fn is_operator(token: &str) -> bool {
-    token.len() == 1 && "+-*/^%s".contains(token)
+    token.len() == 1 && "+-*/\\^%s~".contains(token)
+}
+
+/// Whether `%` is a postfix percentage when `next` follows it: at the end
+/// of an argument or in front of another operator (`50% + 1`). Anything
+/// else after it, such as a number, a name or `(`, makes it modulo.
+fn ends_percent(next: &str) -> bool {
+    [",", ")", "]", "+", "-", "*", "/", "\\", "^"].contains(&next)
+}
+
+fn ends_in_binary_operator(line: &str) -> bool {
+    line.split_whitespace()
+        .last()
+        .is_some_and(|word| word.len() == 1 && "+-*/\\^".contains(word))
 }
 
 fn is_number(token: &str) -> bool {
     token.parse::<f64>().is_ok()
 }
 
+fn is_identifier(token: &str) -> bool {
+    token.starts_with(|c: char| c.is_alphabetic() || c == '_') && !is_operator(token)
+}
+
+/// Checks that every `(` and `[` is closed by the matching bracket, naming
+/// the offending one and its position (counted from 1) if not. Text in
+/// quotes is skipped.
+fn check_brackets(input: &str) -> Result<(), String> {
+    let mut open: Vec<(char, usize)> = Vec::new();
+    let mut in_text = false;
+    let mut previous = ' ';
+    for (i, ch) in input.chars().enumerate() {
+        let position = i + 1;
+        // A `"` right after a digit marks seconds, as in 45°30'15".
+        let seconds = ch == '"' && previous.is_ascii_digit() && !in_text;
+        previous = ch;
+        match ch {
+            '"' if seconds => {}
+            '"' => in_text = !in_text,
+            _ if in_text => {}
+            '(' | '[' => open.push((ch, position)),
+            ')' | ']' => {
+                let expected = if ch == ')' { '(' } else { '[' };
+                match open.pop() {
+                    Some((opener, _)) if opener == expected => {}
+                    Some((opener, at)) => {
+                        return Err(format!(
+                            "'{}' at position {} does not match '{}' at position {}",
+                            ch, position, opener, at
+                        ));
+                    }
+                    None => {
+                        return Err(format!(
+                            "'{}' at position {} has no matching '{}'",
+                            ch, position, expected
+                        ));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    match open.pop() {
+        Some((opener, at)) => Err(format!("'{}' at position {} is never closed", opener, at)),
+        None => Ok(()),
+    }
+}
+
 fn tokenize(input: &str) -> Vec<String> {
-    let mut tokens = Vec::new();
+    let mut tokens: Vec<String> = Vec::new();
     let mut current = String::new();
     let chars: Vec<char> = input.chars().collect();
+    let mut i = 0;
 
-    for (i, &ch) in chars.iter().enumerate() {
+    while i < chars.len() {
+        let ch = chars[i];
+        i += 1;
         if ch.is_ascii_digit() || ch == '.' {
+            if current.is_empty()
+                && let Some(len) = scan_network(&chars[i - 1..])
+            {
+                tokens.push(chars[i - 1..i - 1 + len].iter().collect());
+                i += len - 1;
+                continue;
+            }
+            if current.is_empty()
+                && let Some(len) = scan_date(&chars[i - 1..])
+            {
+                tokens.push(chars[i - 1..i - 1 + len].iter().collect());
+                i += len - 1;
+                continue;
+            }
+            if (current.is_empty() || current == "-")
+                && let Some(len) = scan_mixed(&chars[i - 1..])
+            {
+                current.extend(&chars[i - 1..i - 1 + len]);
+                tokens.push(current.clone());
+                current.clear();
+                i += len - 1;
+                continue;
+            }
+            if (current.is_empty() || current == "-")
+                && let Some(len) = scan_dms(&chars[i - 1..])
+            {
+                current.extend(&chars[i - 1..i - 1 + len]);
+                tokens.push(current.clone());
+                current.clear();
+                i += len - 1;
+                continue;
+            }
+            if (current.is_empty() || current == "-")
+                && let Some(len) = scan_duration(&chars[i - 1..])
+            {
+                current.extend(&chars[i - 1..i - 1 + len]);
+                tokens.push(current.clone());
+                current.clear();
+                i += len - 1;
+                continue;
+            }
             current.push(ch);
-        } else if "+-*/^%()s".contains(ch) {
+        } else if ch == '"' {
+            if !current.is_empty() {
+                tokens.push(current.clone());
+                current.clear();
+            }
+            let Some(len) = chars[i..].iter().position(|&c| c == '"') else {
+                return vec!["Error: unterminated string".to_string()];
+            };
+            tokens.push(chars[i - 1..i + len + 1].iter().collect());
+            i += len + 1;
+        } else if ch.is_alphabetic() || ch == '_' {
+            if !current.is_empty() {
+                tokens.push(current.clone());
+                current.clear();
+            }
+            let mut word = ch.to_string();
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                word.push(chars[i]);
+                i += 1;
+            }
+            tokens.push(word);
+        } else if "+-*/^%()[],".contains(ch) {
+            let unary = current.is_empty()
+                && match tokens.last() {
+                    None => true,
+                    Some(prev) => {
+                        (is_operator(prev) && prev != "s")
+                            || ["(", "[", ","].contains(&prev.as_str())
+                    }
+                };
+            // `**` is another way to write `^`; `//` is floor division,
+            // kept internally as `\`.
+            if (ch == '*' || ch == '/') && chars.get(i) == Some(&ch) {
+                if !current.is_empty() {
+                    tokens.push(current.clone());
+                    current.clear();
+                }
+                tokens.push(if ch == '*' { "^" } else { "\\" }.to_string());
+                i += 1;
+                continue;
+            }
+            // `/26` where an operand belongs is a network prefix, not division.
+            if ch == '/'
+                && unary
+                && let Some(len) = scan_network(&chars[i - 1..])
+            {
+                tokens.push(chars[i - 1..i - 1 + len].iter().collect());
+                i += len - 1;
+                continue;
+            }
             if ch == '-' {
-                let prev = if i > 0 { chars[i - 1] } else { ' ' };
-                if i == 0 || "+-*/^%(s".contains(prev) {
-                    current.push(ch);
+                // A minus sign on a number is part of the literal; in front
+                // of anything else it becomes the negation operator `~`.
+                if unary {
+                    if chars
+                        .get(i)
+                        .is_some_and(|next| next.is_ascii_digit() || *next == '.')
+                    {
+                        current.push(ch);
+                    } else {
+                        tokens.push("~".to_string());
+                    }
                     continue;
                 }
             }
@@ -105,70 +436,624 @@ fn tokenize(input: &str) -> Vec<String> {
     tokens
 }
 
-fn apply_operator(numbers: &mut Vec<f64>, operators: &mut Vec<char>) -> Result<(), String> {
-    let op = operators.pop().ok_or("No operator")?;
+/// Entries of the shunting-yard operator stack.
+enum Pending {
+    Op(char),
+    Paren,
+    /// An open function call: name, first output slot of its arguments
+    /// and the number of commas seen so far.
+    Call(String, usize, usize),
+    /// An open `[...]` list literal, tracked like a call to `list()`.
+    Bracket(usize, usize),
+}
+
+fn apply_operator(numbers: &mut Vec<Expr>, operators: &mut Vec<Pending>) -> Result<(), String> {
+    let op = match operators.pop() {
+        Some(Pending::Op(op)) => op,
+        _ => return Err("No operator".to_string()),
+    };
 
-    if op == 's' {
+    if op == 's' || op == '~' {
         let a = numbers.pop().ok_or("Missing opersand")?;
-        let result = calculate(a, op, 0.0)?;
-        numbers.push(result);
+        let op = if op == '~' { '-' } else { op };
+        numbers.push(Expr::Unary(op, Box::new(a)));
     } else {
         let b = numbers.pop().ok_or("Missing opersand")?;
         let a = numbers.pop().ok_or("Missing opersand")?;
-        let result = calculate(a, op, b)?;
-        numbers.push(result);
+        numbers.push(Expr::Binary(op, Box::new(a), Box::new(b)));
+    }
+    Ok(())
+}
+
+/// Replaces the arguments of a call that just closed with its `Expr::Call`.
+fn close_call(
+    numbers: &mut Vec<Expr>,
+    name: String,
+    start: usize,
+    commas: usize,
+) -> Result<(), String> {
+    let count = numbers.len().checked_sub(start);
+    if count != Some(commas + 1) && !(count == Some(0) && commas == 0) {
+        return Err(format!("Malformed arguments to {}()", name));
+    }
+    let args = numbers.split_off(start);
+    numbers.push(Expr::Call(name, args));
+    Ok(())
+}
+
+/// Applies pending operators down to the innermost `(` or open call.
+fn unwind_to_paren(numbers: &mut Vec<Expr>, operators: &mut Vec<Pending>) -> Result<(), String> {
+    while let Some(Pending::Op(_)) = operators.last() {
+        apply_operator(numbers, operators)?;
     }
     Ok(())
 }
 
-fn evaluate_expression(input: &str) -> Result<f64, String> {
+fn parse_expression(input: &str) -> Result<Expr, String> {
+    check_brackets(input)?;
     let tokens = tokenize(input);
-    let mut numbers: Vec<f64> = Vec::new();
-    let mut operators: Vec<char> = Vec::new();
+    let mut numbers: Vec<Expr> = Vec::new();
+    let mut operators: Vec<Pending> = Vec::new();
+    let mut tokens = tokens.into_iter().peekable();
 
-    for token in tokens {
-        if is_number(&token) {
+    while let Some(token) = tokens.next() {
+        if let Some(address) = parse_address(&token) {
+            numbers.push(Expr::Literal(Value::Address(address)));
+        } else if let Some((address, prefix)) = parse_network(&token) {
+            numbers.push(Expr::Literal(Value::Network(address, prefix)));
+        } else if let Some(n) = parse_mixed(&token) {
+            numbers.push(Expr::Literal(Value::Number(n)));
+        } else if let Some(degrees) = parse_dms(&token) {
+            let degrees = Box::new(Expr::Literal(Value::Number(degrees)));
+            numbers.push(Expr::Unary('°', degrees));
+        } else if is_number(&token) {
             let num = token
                 .parse::<f64>()
                 .map_err(|_| format!("Invalid number: {}", token))?;
-            numbers.push(num);
+            numbers.push(Expr::Literal(Value::Number(num)));
+        } else if let Some(text) = token.strip_prefix('"') {
+            let text = text.strip_suffix('"').unwrap_or(text);
+            numbers.push(Expr::Literal(Value::Text(text.to_string())));
+        } else if let Some(seconds) = parse_date(&token) {
+            numbers.push(Expr::Literal(Value::Time(seconds)));
+        } else if let Some(seconds) = parse_duration(&token) {
+            numbers.push(Expr::Literal(Value::Duration(seconds)));
+        } else if token == "%" && tokens.peek().is_none_or(|next| ends_percent(next)) {
+            let a = numbers.pop().ok_or("Missing operand before %")?;
+            numbers.push(Expr::Unary('%', Box::new(a)));
         } else if is_operator(&token) {
             let op = token.chars().next().unwrap();
-            while let Some(&top) = operators.last() {
-                if top != '(' && precedence(top) >= precedence(op) {
+            // Prefix negation has no left operand, so nothing is applied yet.
+            while let Some(&Pending::Op(top)) = operators.last()
+                && op != '~'
+            {
+                if precedence(top) >= precedence(op) {
                     apply_operator(&mut numbers, &mut operators)?;
                 } else {
                     break;
                 }
             }
-            operators.push(op);
+            operators.push(Pending::Op(op));
         } else if token == "(" {
-            operators.push('(');
+            operators.push(Pending::Paren);
         } else if token == ")" {
-            while let Some(&top) = operators.last() {
-                if top == '(' {
-                    operators.pop();
-                    break;
+            unwind_to_paren(&mut numbers, &mut operators)?;
+            match operators.pop() {
+                Some(Pending::Call(name, start, commas)) => {
+                    close_call(&mut numbers, name, start, commas)?
+                }
+                Some(Pending::Bracket(..)) => return Err("Missing ']' before ')'".to_string()),
+                Some(Pending::Paren) => {}
+                _ => return Err("Unexpected ')'".to_string()),
+            }
+        } else if token == "[" {
+            operators.push(Pending::Bracket(numbers.len(), 0));
+        } else if token == "]" {
+            unwind_to_paren(&mut numbers, &mut operators)?;
+            match operators.pop() {
+                Some(Pending::Bracket(start, commas)) => {
+                    close_call(&mut numbers, "list".to_string(), start, commas)?
                 }
-                apply_operator(&mut numbers, &mut operators)?;
+                _ => return Err("Unexpected ']'".to_string()),
+            }
+        } else if token == "," {
+            unwind_to_paren(&mut numbers, &mut operators)?;
+            match operators.last_mut() {
+                Some(Pending::Call(_, _, commas)) | Some(Pending::Bracket(_, commas)) => {
+                    *commas += 1
+                }
+                _ => return Err("Comma outside of a function call".to_string()),
             }
         } else if token.starts_with("Error:") {
             return Err(token);
+        } else if is_identifier(&token) && tokens.peek().is_some_and(|next| next == "(") {
+            tokens.next();
+            operators.push(Pending::Call(token, numbers.len(), 0));
+        } else if is_identifier(&token) {
+            numbers.push(Expr::Name(token));
         } else {
             return Err(format!("Unknown token: {}", token));
         }
     }
-    while !operators.is_empty() {
-        apply_operator(&mut numbers, &mut operators)?;
+    while let Some(top) = operators.last() {
+        match top {
+            Pending::Op(_) => apply_operator(&mut numbers, &mut operators)?,
+            Pending::Paren => return Err("Missing ')'".to_string()),
+            Pending::Call(name, _, _) => return Err(format!("Missing ')' after {}(", name)),
+            Pending::Bracket(..) => return Err("Missing ']'".to_string()),
+        }
     }
 
     if numbers.len() == 1 {
-        Ok(numbers[0])
+        Ok(numbers.remove(0))
     } else {
         Err("Error: Incorrect input".to_string())
     }
 }
 
+fn evaluate_expression(input: &str, env: &mut Env) -> Result<Value, String> {
+    eval(&parse_expression(input)?, env)
+}
+
+/// Evaluates each part as an expression that must give a number.
+fn evaluate_numbers<'a>(
+    command: &str,
+    parts: impl Iterator<Item = &'a str>,
+    env: &mut Env,
+) -> Result<Vec<f64>, String> {
+    parts
+        .map(|part| match evaluate_expression(part, env)? {
+            Value::Number(n) => Ok(n),
+            other => Err(format!("{} expects numbers, got {}", command, other.kind())),
+        })
+        .collect()
+}
+
+/// `set <name> <value>` - changes a setting.
+fn set_command(args: &str, env: &mut Env) -> Result<String, String> {
+    let Some((name, value)) = args.trim().split_once(' ') else {
+        return Err("Usage: set <name> <value>".to_string());
+    };
+    if value.trim() == "off" {
+        env.settings.reset(name)?;
+        return Ok(format!("{} reset", name));
+    }
+    if settings::TEXT_SETTINGS.contains(&name) {
+        env.settings.set_text(name, value.trim())?;
+        return Ok(format!("{} updated", name));
+    }
+    let value = evaluate_numbers("set", std::iter::once(value), env)?[0];
+    env.settings.set(name, value)?;
+    Ok(format!("{} updated", name))
+}
+
+/// Applies the `set` lines of `.calcrc`, reporting bad lines as warnings.
+fn load_rc(env: &mut Env) {
+    let Ok(content) = fs::read_to_string(".calcrc") else {
+        return;
+    };
+    for (number, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let result = if let Some(args) = line.strip_prefix("set ") {
+            set_command(args, env).map(|_| ())
+        } else if let Some(args) = line.strip_prefix("data ") {
+            data_command(args, env).map(|_| ())
+        } else {
+            Err(format!("unsupported line: {}", line))
+        };
+        if let Err(e) = result {
+            eprintln!("Warning: .calcrc line {}: {}", number + 1, e);
+        }
+    }
+    for origin in env.origins.values_mut() {
+        origin.in_rc = true;
+    }
+}
+
+/// `store <name>` - binds the last result to a variable.
+fn store_command(name: &str, history: &[HistoryEntry], env: &mut Env) -> Result<String, String> {
+    let name = name.trim();
+    if !is_identifier(name) || name == "MR" {
+        return Err(format!("'{}' cannot be used as a variable name", name));
+    }
+    let entry = history.last().ok_or("No result to store yet")?;
+    env.define(name, entry.result.clone(), &format!("store {}", name));
+    Ok(format!("{} = {}", name, entry.result.show(&env.settings)))
+}
+
+/// `show <name>` - the value behind a name and where it came from.
+fn show_command(name: &str, env: &Env) -> Result<String, String> {
+    let name = name.trim();
+    let (value, about) = if name == "MR" {
+        let about = "memory register (M+, M-, MC)".to_string();
+        (Value::Number(env.memory), about)
+    } else if let Some(value) = env.variables.get(name) {
+        let about = match env.origins.get(name) {
+            Some(origin) => format!(
+                "{}, from `{}` {}",
+                value.kind(),
+                origin.command,
+                if origin.in_rc {
+                    "in .calcrc"
+                } else {
+                    "in this session"
+                }
+            ),
+            None => value.kind().to_string(),
+        };
+        (value.clone(), about)
+    } else if let Some(value) = functions::constant(name) {
+        (value, "built-in constant".to_string())
+    } else {
+        return Err(format!("Unknown name: {}", name));
+    };
+    Ok(format!(
+        "{} = {}\n  {}",
+        name,
+        value.show(&env.settings),
+        about
+    ))
+}
+
+/// `split <bill>, <rate>[, <people>]` - tip, total and share per person.
+fn split_command(args: &str, env: &mut Env) -> Result<String, String> {
+    let values = evaluate_numbers("split", args.split(','), env)?;
+    let (bill, rate, people) = match values.as_slice() {
+        [bill, rate] => (*bill, *rate, 1.0),
+        [bill, rate, people] => (*bill, *rate, *people),
+        _ => return Err("Usage: split <bill>, <tip rate>[, <people>]".to_string()),
+    };
+
+    Ok(bill::split_bill(bill, rate, people)?.parts().join("\n"))
+}
+
+/// `amortize <principal> <rate> <years> [> file.csv]` - monthly repayment
+/// table, optionally exported as CSV instead of printed.
+fn amortize_command(args: &str, env: &mut Env) -> Result<String, String> {
+    let (args, path) = match args.split_once('>') {
+        Some((args, path)) => (args, Some(path.trim())),
+        None => (args, None),
+    };
+    let values = evaluate_numbers("amortize", args.split_whitespace(), env)?;
+    let [principal, rate, years] = values[..] else {
+        return Err("Usage: amortize <principal> <rate> <years> [> file.csv]".to_string());
+    };
+
+    let rows = amortize::schedule(principal, rate, years)?;
+    match path {
+        Some("") => Err("Missing file name after '>'".to_string()),
+        Some(path) => {
+            export::write_csv(path, &amortize::csv_records(&rows))?;
+            Ok(format!("{} payments exported to '{}'", rows.len(), path))
+        }
+        None => Ok(amortize::format_table(&rows)),
+    }
+}
+
+/// `data load <file.csv> [col=<n|name>] [as <name>]` - reads a numeric CSV
+/// column into a list variable (`data` unless named).
+fn data_command(args: &str, env: &mut Env) -> Result<String, String> {
+    let usage = "Usage: data load <file.csv> [col=<number|name>] [as <name>]";
+    let mut words = args.split_whitespace();
+    if words.next() != Some("load") {
+        return Err(usage.to_string());
+    }
+    let path = words.next().ok_or(usage)?;
+    let mut column = "1";
+    let mut name = "data";
+    while let Some(word) = words.next() {
+        if let Some(col) = word.strip_prefix("col=") {
+            column = col;
+        } else if word == "as" {
+            name = words.next().filter(|n| is_identifier(n)).ok_or(usage)?;
+        } else {
+            return Err(usage.to_string());
+        }
+    }
+
+    let values = data::load_column(path, column)?;
+    let message = format!("Loaded {} values into '{}'", values.len(), name);
+    env.define(name, Value::List(values), &format!("data {}", args.trim()));
+    Ok(message)
+}
+
+/// `words [money] <expr>` - spells out the value of an expression.
+fn words_command(args: &str, env: &mut Env) -> Result<String, String> {
+    let (money, expression) = match args.trim().strip_prefix("money ") {
+        Some(rest) => (true, rest),
+        None => (false, args),
+    };
+    match evaluate_expression(expression, env)? {
+        Value::Number(n) if money => words::money_to_words(n),
+        Value::Number(n) => words::number_to_words(n),
+        other => Err(format!("Cannot spell out a {}", other.kind())),
+    }
+}
+
+/// `M+ [expr]` / `M- [expr]` - adds the value of `expr`, or the last result
+/// when there is none, to the memory register (or subtracts it).
+fn memory_command(
+    op: char,
+    args: &str,
+    history: &[HistoryEntry],
+    env: &mut Env,
+) -> Result<String, String> {
+    let value = if args.trim().is_empty() {
+        history
+            .last()
+            .map(|entry| entry.result.clone())
+            .ok_or("No result to add to memory yet")?
+    } else {
+        evaluate_expression(args, env)?
+    };
+    let Value::Number(n) = value else {
+        return Err(format!("Memory holds numbers only, got {}", value.kind()));
+    };
+    env.memory = calculate(env.memory, op, n)?;
+    Ok(format!("M = {}", env.memory))
+}
+
+/// Runs a line in RPN mode and shows the stack. A line that ends in an
+/// operator records its result in the history.
+fn rpn_command(
+    stack: &mut rpn::Stack,
+    line: &str,
+    history: &mut Vec<HistoryEntry>,
+) -> Result<String, String> {
+    stack.run(line)?;
+    let calculates = line.split_whitespace().last().is_some_and(is_operator);
+    if calculates && let Some(top) = stack.top() {
+        history.push(HistoryEntry::new_rpn(line.to_string(), top.clone()));
+    }
+    Ok(stack.describe())
+}
+
+/// `replay` - evaluates every history entry again and lists those whose
+/// result is no longer the same. RPN lines run on an empty stack; those
+/// that need values left by earlier lines are skipped. The replay works on
+/// a copy of the session, so it defines no variables and leaves the random
+/// numbers after `seed` as they were.
+fn replay_command(history: &[HistoryEntry], env: &Env) -> Result<String, String> {
+    if history.is_empty() {
+        return Err("History is empty".to_string());
+    }
+    let env = &mut env.clone();
+    let mut changes = vec![];
+    let mut skipped = 0;
+    for (i, entry) in history.iter().enumerate() {
+        let replayed = if entry.rpn {
+            let mut stack = rpn::Stack::default();
+            match stack.run(&entry.expression).map(|_| stack.top().cloned()) {
+                Ok(Some(result)) => Ok(result),
+                _ => {
+                    skipped += 1;
+                    continue;
+                }
+            }
+        } else {
+            evaluate_expression(&entry.expression, env)
+        };
+        let now = match replayed {
+            Ok(result) if result == entry.result => continue,
+            Ok(result) => result.show(&env.settings),
+            Err(e) => format!("error: {}", e),
+        };
+        changes.push(format!(
+            "{}. {}: {} -> {}",
+            i + 1,
+            entry.expression,
+            entry.result.show(&env.settings),
+            now
+        ));
+    }
+    let checked = history.len() - skipped;
+    let mut summary = if changes.is_empty() {
+        format!("All {} result(s) unchanged", checked)
+    } else {
+        format!("{} of {} result(s) changed", changes.len(), checked)
+    };
+    if skipped > 0 {
+        summary.push_str(&format!(
+            " ({} RPN line(s) skipped, as they need values from earlier lines)",
+            skipped
+        ));
+    }
+    if !changes.is_empty() {
+        summary.push(':');
+    }
+    changes.insert(0, summary);
+    Ok(changes.join("\n"))
+}
+
+/// `full` - the last result with every digit the double holds, and its
+/// hex-float form.
+fn full_command(history: &[HistoryEntry]) -> Result<String, String> {
+    match history.last().map(|entry| &entry.result) {
+        Some(Value::Number(n)) => Ok(format!(
+            "= {}\n  hex {}",
+            float::exact_decimal(*n),
+            float::hex_float(*n)
+        )),
+        Some(other) => Err(format!("full shows numbers only, got {}", other.kind())),
+        None => Err("No calculations yet".to_string()),
+    }
+}
+
+/// A line starting with an operator (`+ 5`, `* 1.21`) continues from the
+/// last result, as if it had been typed in front. `-` and `/` need a space
+/// after them, since `-5` is a negative number and `/24` a network.
+fn continuation(input: &str, history: &[HistoryEntry]) -> Result<Option<String>, String> {
+    let mut chars = input.chars();
+    let continues = match (chars.next(), chars.next()) {
+        (Some('+' | '*' | '^' | '%'), _) => true,
+        (Some('-' | '/'), Some(next)) => next.is_whitespace(),
+        _ => false,
+    };
+    if !continues {
+        return Ok(None);
+    }
+    match history.last().map(|entry| &entry.result) {
+        Some(Value::Number(n)) => Ok(Some(format!("{} {}", n, input))),
+        Some(other) => Err(format!("Cannot continue from a {} result", other.kind())),
+        None => Err("No previous result to continue from".to_string()),
+    }
+}
+
+/// Most runs `repeat` will do.
+const MAX_REPEATS: usize = 1_000_000;
+
+/// `repeat <n> [hist] <expression>` - evaluates the expression `n` times,
+/// for experiments with `rand()`, and summarises the results. With
+/// `accessible` the histogram gives counts only, without bars.
+fn repeat_command(args: &str, env: &mut Env, accessible: bool) -> Result<String, String> {
+    let usage = "Usage: repeat <n> [hist] <expression>";
+    let (count, rest) = args.trim().split_once(' ').ok_or(usage)?;
+    let count = count
+        .parse::<usize>()
+        .ok()
+        .filter(|n| (2..=MAX_REPEATS).contains(n))
+        .ok_or(format!("repeat runs 2 to {} times", MAX_REPEATS))?;
+    let (hist, expression) = match rest.trim_start().strip_prefix("hist ") {
+        Some(expression) => (true, expression),
+        None => (false, rest),
+    };
+    let expr = parse_expression(expression)?;
+    let mut results = Vec::with_capacity(count);
+    for _ in 0..count {
+        match eval(&expr, env)? {
+            Value::Number(n) => results.push(n),
+            other => return Err(format!("repeat needs a number, got {}", other.kind())),
+        }
+    }
+    let show = |n: f64| env.settings.format_number(n);
+    let mut text = format!(
+        "{} runs: mean {}, stddev {}, min {}, max {}",
+        count,
+        show(stats::mean(&results)?),
+        show(stats::stddev(&results)?),
+        show(stats::min(&results)?),
+        show(stats::max(&results)?)
+    );
+    if hist {
+        let low = stats::min(&results)?;
+        let width = (stats::max(&results)? - low) / 10.0;
+        let counts = stats::histogram(&results, 10);
+        let most = counts.iter().copied().max().unwrap_or(1);
+        for (i, n) in counts.iter().enumerate() {
+            let from = low + i as f64 * width;
+            if accessible {
+                text.push_str(&format!("\nfrom {:.4}: {} runs", from, n));
+            } else {
+                let bar = "█".repeat(n * 40 / most);
+                text.push_str(&format!("\n{:>12.4}  {} {}", from, bar, n));
+            }
+        }
+    }
+    Ok(text)
+}
+
+/// `trend [n]` - sparkline of the last `n` numeric results (default 20).
+/// With `accessible` only the summary is given.
+fn trend_command(args: &str, history: &[HistoryEntry], accessible: bool) -> Result<String, String> {
+    let count = match args.trim() {
+        "" => 20,
+        n => n
+            .parse::<usize>()
+            .ok()
+            .filter(|&n| n >= 2)
+            .ok_or("Usage: trend [n], with n at least 2")?,
+    };
+    let numbers: Vec<f64> = history
+        .iter()
+        .filter_map(|entry| match entry.result {
+            Value::Number(n) => Some(n),
+            _ => None,
+        })
+        .collect();
+    let recent = &numbers[numbers.len().saturating_sub(count)..];
+    if recent.len() < 2 {
+        return Err("trend needs at least two numeric results in the history".to_string());
+    }
+    let low = recent.iter().copied().fold(f64::INFINITY, f64::min);
+    let high = recent.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    let summary = format!(
+        "last {} results: {} -> {} (min {}, max {})",
+        recent.len(),
+        recent[0],
+        recent[recent.len() - 1],
+        low,
+        high
+    );
+    if accessible {
+        return Ok(summary);
+    }
+    Ok(format!("{}  {}", sparkline::sparkline(recent), summary))
+}
+
+/// `plot(expr, x, a, b)` - draws the expression as a character graph, or
+/// with `accessible` lists its values.
+fn plot_command(input: &str, env: &mut Env, accessible: bool) -> Result<String, String> {
+    match parse_expression(input)? {
+        Expr::Call(name, args) if name == "plot" && accessible => plot::table(&args, env),
+        Expr::Call(name, args) if name == "plot" => plot::plot(&args, env),
+        _ => Err("Usage: plot(<expression>, <variable>, <from>, <to>)".to_string()),
+    }
+}
+
+/// `explain <expression>` - evaluates step by step, showing each
+/// intermediate expression.
+fn explain_command(args: &str, env: &mut Env) -> Result<String, String> {
+    let expr = parse_expression(args)?;
+    Ok(explain::explain(expr, env)?.join("\n"))
+}
+
+/// `simplify <expression>` - folds constants and collects like terms,
+/// leaving names symbolic.
+fn simplify_command(args: &str) -> Result<String, String> {
+    let expr = parse_expression(args)?;
+    Ok(format!("= {}", simplify::simplify(&expr)))
+}
+
+/// `fmt <expression>` - reprints the expression with consistent spacing
+/// and only the parentheses precedence needs.
+fn fmt_command(args: &str) -> Result<String, String> {
+    Ok(parse_expression(args)?.to_string())
+}
+
+/// Appends a failed expression to the error log as a tab-separated line:
+/// time, kind of error (`syntax` or `evaluation`), input and message.
+fn log_error(path: &str, input: &str, error: &str) -> Result<(), String> {
+    let kind = match parse_expression(input) {
+        Ok(_) => "evaluation",
+        Err(_) => "syntax",
+    };
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| format!("Cannot open error log {}: {}", path, e))?;
+    writeln!(
+        file,
+        "{}\t{}\t{}\t{}",
+        format_timestamp(timestamp::now()),
+        kind,
+        input,
+        error
+    )
+    .map_err(|e| format!("Cannot write error log {}: {}", path, e))
+}
+
+/// Evaluates an expression, also giving the parsed expression: its
+/// canonical form goes to the history, and `--accessible` reads it out.
+fn evaluate_line(input: &str, env: &mut Env) -> Result<(Expr, Value), String> {
+    let expr = parse_expression(input)?;
+    let result = eval(&expr, env)?;
+    Ok((expr, result))
+}
+
 fn calculate(a: f64, op: char, b: f64) -> Result<f64, String> {
     match op {
         '+' => Ok(a + b),
@@ -181,6 +1066,7 @@ fn calculate(a: f64, op: char, b: f64) -> Result<f64, String> {
                 Ok(a / b)
             }
         }
+        '\\' => calculate(a, '/', b).map(f64::floor),
         '^' => Ok(a.powf(b)),
         '%' => Ok(a % b),
         's' => Ok(a.sqrt()),
@@ -188,29 +1074,92 @@ fn calculate(a: f64, op: char, b: f64) -> Result<f64, String> {
     }
 }
 
-fn get_input() -> String {
-    print!("> ");
-    io::stdout().flush().unwrap();
-
-    let mut input = String::new();
-    io::stdin()
-        .read_line(&mut input)
-        .expect("Failed to read line");
+/// Reads a line, with hints from earlier input on a terminal. End of input
+/// and Ctrl-C end the session like `exit`.
+fn get_input(editor: &mut Editor<Hints, DefaultHistory>) -> String {
+    // The editor draws the prompt itself only on a terminal.
+    if !io::stdin().is_terminal() {
+        print!("> ");
+        io::stdout().flush().unwrap();
+    }
+    match editor.readline("> ") {
+        Ok(line) => {
+            let input = line.trim().to_string();
+            if let Some(hints) = editor.helper_mut() {
+                hints.add(&input);
+            }
+            input
+        }
+        Err(ReadlineError::Eof | ReadlineError::Interrupted) => "exit".to_string(),
+        Err(e) => panic!("Failed to read line: {}", e),
+    }
+}
 
-    input.trim().to_string()
+/// Prints a line and records it in the session transcript.
+macro_rules! say {
+    ($transcript:expr, $($arg:tt)*) => {{
+        let text = format!($($arg)*);
+        println!("{}", text);
+        $transcript.output(&text);
+    }};
 }
 
 fn main() {
-    println!("{}", "============================".cyan());
-    println!("{}", "||   CLI Calculator v1.0  ||".cyan());
-    println!("{}", "============================\n".cyan());
+    // `--accessible` is for screen readers: no colours or drawn banner, and
+    // results read out in words.
+    let accessible = std::env::args().skip(1).any(|arg| arg == "--accessible");
+    if accessible {
+        colored::control::set_override(false);
+    }
+    let mut transcript = Transcript::default();
+    if accessible {
+        say!(transcript, "CLI Calculator version 1.0, accessible mode\n");
+    } else {
+        say!(transcript, "{}", "============================".cyan());
+        say!(transcript, "{}", "||   CLI Calculator v1.0  ||".cyan());
+        say!(transcript, "{}", "============================\n".cyan());
+    }
 
-    println!("Type {} for available commands\n", "'help'".yellow());
+    say!(
+        transcript,
+        "Type {} for available commands\n",
+        "'help'".yellow()
+    );
 
-    let mut history: Vec<HistoryEntry> = load_history();
+    // Where the history is saved; `None` if the file there cannot be read
+    // safely, so it is left alone.
+    let mut history_file = Some(HISTORY_FILE);
+    let mut history: Vec<HistoryEntry> = match load_history(HISTORY_FILE) {
+        Ok((entries, 0)) => entries,
+        Ok((entries, skipped)) => {
+            say!(
+                transcript,
+                "{} {} lines of {} could not be read; a backup is kept when it is next saved\n",
+                "Warning:".yellow(),
+                skipped,
+                HISTORY_FILE
+            );
+            entries
+        }
+        Err(e) => {
+            say!(
+                transcript,
+                "{} {}; it will not be overwritten this session\n",
+                "Warning:".yellow(),
+                e.yellow()
+            );
+            history_file = None;
+            Vec::new()
+        }
+    };
+    let mut env = Env::default();
+    load_rc(&mut env);
+    // The RPN stack while `mode rpn` is on.
+    let mut rpn: Option<rpn::Stack> = None;
 
     if !history.is_empty() {
-        println!(
+        say!(
+            transcript,
             "{} {} {}",
             "Loaded".yellow().italic(),
             history.len().to_string().green(),
@@ -218,98 +1167,639 @@ fn main() {
         );
     }
 
+    let mut editor = Editor::new().expect("Failed to set up line editing");
+    editor.set_helper(Some(Hints::new(
+        history
+            .iter()
+            .map(|entry| entry.expression.clone())
+            .collect(),
+    )));
+
     loop {
-        let input = get_input();
+        let input = get_input(&mut editor);
+        transcript.input(&input);
 
         match input.as_str() {
             "exit" | "quit" => {
-                println!("{}", "Goodbye!".green().bold());
+                say!(transcript, "{}", "Goodbye!".green().bold());
                 break;
             }
 
             "history" => {
                 if history.is_empty() {
-                    println!("{}\n", "History is empty".yellow());
+                    say!(transcript, "{}\n", "History is empty".yellow());
                 } else {
-                    println!("{}", "Calculation history:".cyan().bold());
+                    say!(transcript, "{}", "Calculation history:".cyan().bold());
                     for (i, entry) in history.iter().enumerate() {
-                        println!("{}. {}", i + 1, entry);
+                        say!(transcript, "{}. {}", i + 1, entry.show(&env.settings));
                     }
-                    println!();
+                    say!(transcript, "");
                 }
             }
 
             "clear" => {
                 history.clear();
-                println!("{}\n", "History cleared".yellow());
+                say!(transcript, "{}\n", "History cleared".yellow());
             }
 
-            "save" => match save_history(&history) {
-                Ok(_) => println!("{}\n", "History saved to 'history.txt'".green()),
-                Err(e) => println!("{} {}\n", "Error: ".red(), e.red()),
+            "save" => match history_file.map(|path| save_history(path, &history)) {
+                Some(Ok(_)) => say!(transcript, "{}\n", "History saved to 'history.txt'".green()),
+                Some(Err(e)) => say!(transcript, "{} {}\n", "Error: ".red(), e.red()),
+                None => say!(
+                    transcript,
+                    "{} {}\n",
+                    "Error: ".red(),
+                    "history.txt is from a newer version and is left alone".red()
+                ),
             },
 
             "last" => {
                 if let Some(entry) = history.last() {
-                    println!("{} {}\n", "Last calculation: ".cyan(), entry);
+                    say!(
+                        transcript,
+                        "{} {}\n",
+                        "Last calculation: ".cyan(),
+                        entry.show(&env.settings)
+                    );
                 } else {
-                    println!("{}\n", "No calculations yet".yellow());
+                    say!(transcript, "{}\n", "No calculations yet".yellow());
+                }
+            }
+            "mode" => {
+                let mode = if rpn.is_some() { "rpn" } else { "algebraic" };
+                say!(transcript, "{}\n", format!("Input mode: {}", mode).cyan());
+            }
+            "mode rpn" => {
+                rpn.get_or_insert_with(rpn::Stack::default);
+                say!(
+                    transcript,
+                    "{}\n",
+                    "RPN mode: type numbers and operators like 3 4 + (drop, swap, dup, clst)"
+                        .green()
+                );
+            }
+            "mode algebraic" => {
+                rpn = None;
+                say!(transcript, "{}\n", "Algebraic mode".green());
+            }
+            "MC" => {
+                env.memory = 0.0;
+                say!(transcript, "{}\n", "Memory cleared".yellow());
+            }
+            _ if ["M+", "M-"].contains(&input.split(' ').next().unwrap_or_default()) => {
+                let op = if input.starts_with("M+") { '+' } else { '-' };
+                match memory_command(op, &input[2..], &history, &mut env) {
+                    Ok(text) => say!(transcript, "{}\n", text.green()),
+                    Err(e) => say!(transcript, "{} {}\n", "Error:".red(), e.red()),
+                }
+            }
+            _ if input.starts_with("transcript ") => match transcript.save(input[11..].trim()) {
+                Ok(text) => say!(transcript, "{}\n", text.green()),
+                Err(e) => say!(transcript, "{} {}\n", "Error:".red(), e.red()),
+            },
+            _ if input.starts_with("store ") => {
+                match store_command(&input[6..], &history, &mut env) {
+                    Ok(text) => say!(transcript, "{}\n", text.green()),
+                    Err(e) => say!(transcript, "{} {}\n", "Error:".red(), e.red()),
+                }
+            }
+            _ if input.starts_with("show ") => match show_command(&input[5..], &env) {
+                Ok(text) => say!(transcript, "{}\n", text.green()),
+                Err(e) => say!(transcript, "{} {}\n", "Error:".red(), e.red()),
+            },
+            "replay" => match replay_command(&history, &env) {
+                Ok(text) => say!(transcript, "{}\n", text.green()),
+                Err(e) => say!(transcript, "{} {}\n", "Error:".red(), e.red()),
+            },
+            "full" => match full_command(&history) {
+                Ok(text) => say!(transcript, "{}\n", text.green()),
+                Err(e) => say!(transcript, "{} {}\n", "Error:".red(), e.red()),
+            },
+            "trend" => match trend_command("", &history, accessible) {
+                Ok(text) => say!(transcript, "{}\n", text.green()),
+                Err(e) => say!(transcript, "{} {}\n", "Error:".red(), e.red()),
+            },
+            _ if input.starts_with("repeat ") => {
+                match repeat_command(&input[7..], &mut env, accessible) {
+                    Ok(text) => say!(transcript, "{}\n", text.green()),
+                    Err(e) => say!(transcript, "{} {}\n", "Error:".red(), e.red()),
+                }
+            }
+            _ if input.starts_with("trend ") => {
+                match trend_command(&input[6..], &history, accessible) {
+                    Ok(text) => say!(transcript, "{}\n", text.green()),
+                    Err(e) => say!(transcript, "{} {}\n", "Error:".red(), e.red()),
+                }
+            }
+            _ if input.starts_with("plot(") => match plot_command(&input, &mut env, accessible) {
+                Ok(text) => say!(transcript, "{}\n", text.green()),
+                Err(e) => say!(transcript, "{} {}\n", "Error:".red(), e.red()),
+            },
+            _ if input.starts_with("explain ") => match explain_command(&input[8..], &mut env) {
+                Ok(text) => say!(transcript, "{}\n", text.green()),
+                Err(e) => say!(transcript, "{} {}\n", "Error:".red(), e.red()),
+            },
+            _ if input.starts_with("fmt ") => match fmt_command(&input[4..]) {
+                Ok(text) => say!(transcript, "{}\n", text.green()),
+                Err(e) => say!(transcript, "{} {}\n", "Error:".red(), e.red()),
+            },
+            _ if input.starts_with("simplify ") => match simplify_command(&input[9..]) {
+                Ok(text) => say!(transcript, "{}\n", text.green()),
+                Err(e) => say!(transcript, "{} {}\n", "Error:".red(), e.red()),
+            },
+            _ if input.starts_with("words ") => match words_command(&input[6..], &mut env) {
+                Ok(text) => say!(transcript, "{}\n", text.green()),
+                Err(e) => say!(transcript, "{} {}\n", "Error:".red(), e.red()),
+            },
+            _ if input.starts_with("split ") => match split_command(&input[6..], &mut env) {
+                Ok(text) => say!(transcript, "{}\n", text.green()),
+                Err(e) => say!(transcript, "{} {}\n", "Error:".red(), e.red()),
+            },
+            _ if input.starts_with("amortize ") => match amortize_command(&input[9..], &mut env) {
+                Ok(text) => say!(transcript, "{}\n", text.green()),
+                Err(e) => say!(transcript, "{} {}\n", "Error:".red(), e.red()),
+            },
+            "set" => say!(
+                transcript,
+                "{}\n",
+                env.settings.describe().join("\n").cyan()
+            ),
+            _ if input.starts_with("set ") => match set_command(&input[4..], &mut env) {
+                Ok(text) => say!(transcript, "{}\n", text.green()),
+                Err(e) => say!(transcript, "{} {}\n", "Error:".red(), e.red()),
+            },
+            _ if input.starts_with("data ") => match data_command(&input[5..], &mut env) {
+                Ok(text) => say!(transcript, "{}\n", text.green()),
+                Err(e) => say!(transcript, "{} {}\n", "Error:".red(), e.red()),
+            },
+            _ if input.starts_with("seed ") => match input[5..].trim().parse::<u64>() {
+                Ok(seed) => {
+                    env.rng.seed(seed);
+                    say!(
+                        transcript,
+                        "{}\n",
+                        format!("Random seed set to {}", seed).green()
+                    );
                 }
+                Err(_) => say!(
+                    transcript,
+                    "{} {}\n",
+                    "Error:".red(),
+                    "Usage: seed <n>".red()
+                ),
+            },
+            "help finance" => {
+                say!(transcript, "{}", "Financial functions:".bold().magenta());
+                for line in [
+                    "  pmt(rate, nper, pv[, fv, due])  - Payment per period",
+                    "  fv(rate, nper, pmt[, pv, due])  - Future value",
+                    "  pv(rate, nper, pmt[, fv, due])  - Present value",
+                    "  nper(rate, pmt, pv[, fv, due])  - Number of periods",
+                    "  npv(rate, cf1, cf2, ...)        - Net present value, flows from period 1",
+                    "  irr(cf0, cf1, ...)              - Internal rate of return, flows from period 0",
+                    "",
+                    "  rate is per period (0.05/12 for 5% a year paid monthly).",
+                    "  Money you pay out is negative, money you receive is positive:",
+                    "  pmt(0.05/12, 360, 250000) = -1342.05 (you borrow 250000, then pay).",
+                    "  due is 0 for payments at the end of a period (default), 1 for the start.",
+                    "",
+                    "  vat_add(net[, rate])            - Amount plus VAT",
+                    "  vat_remove(gross[, rate])       - Amount without VAT",
+                    "                                    rate such as 21%, default from 'set vat'",
+                    "",
+                    "  amortize <principal> <rate> <years> [> file.csv]",
+                    "                                  - Monthly repayment table, e.g.",
+                    "                                    amortize 250000 5% 30 > loan.csv",
+                ] {
+                    say!(transcript, "{}", line.magenta());
+                }
+                say!(transcript, "");
             }
             "help" => {
-                println!("{}", "Available commands:".bold().magenta());
-                println!(
+                say!(transcript, "{}", "Available commands:".bold().magenta());
+                say!(
+                    transcript,
                     "{}",
                     "  number op number  - Calculate (e.g., 5 + 3)".magenta()
                 );
-                println!("{}", "  Operators         - + - * / % ^  s".magenta());
-                println!(
+                say!(
+                    transcript,
                     "{}",
-                    "  history           - Show calculation history".magenta()
+                    "  Operators         - + - * / // % ^ (or **)  s".magenta()
                 );
-                println!("{}", "  clear             - Clear history".magenta());
-                println!("{}", "  save              - Save history to file".magenta());
-                println!(
+                say!(
+                    transcript,
                     "{}",
-                    "  last              - Show last calculation".magenta()
+                    "  Durations         - 1h30m + 45m, 90min / 4, 5min + 30sec (shown as h:mm:ss)"
+                        .magenta()
+                );
+                say!(
+                    transcript,
+                    "{}",
+                    "  pace(42.195, 3:45:00), speed(km, time), mpg(l100km), l100km(mpg)".magenta()
+                );
+                say!(
+                    transcript,
+                    "{}",
+                    "  Dates (UTC)       - now(), epoch(2024-05-01 12:00), fromepoch(1714557600)"
+                        .magenta()
+                );
+                say!(
+                    transcript,
+                    "{}",
+                    "  Roman numerals    - roman(2024), unroman(MCMXCIX)".magenta()
+                );
+                say!(
+                    transcript,
+                    "{}",
+                    "  Other bases       - tobase(255, 16), tobase(0.1, 2), frombase(\"z\", 36)"
+                        .magenta()
+                );
+                say!(
+                    transcript,
+                    "{}",
+                    "  Bits              - popcount(x), bitget/bitset/bitclear(x, n), rotl/rotr(x, n[, width])"
+                        .magenta()
                 );
-                println!("{}\n", "  exit/quit         - Exit calculator".magenta());
-            }
-            _ => match evaluate_expression(&input) {
-                Ok(result) => {
-                    println!("{} {}\n", "=".green(), result.to_string().green());
-                    let record = HistoryEntry::new(input, result);
-                    history.push(record);
-                }
-                Err(e) => println!("{} {}\n", "Error:".red(), e.red()),
-            },
-        }
-    }
-
-    match save_history(&history) {
-        Ok(_) => {}
-        Err(e) => eprintln!("Warning: failed to save history: {}", e),
-    }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn test_addition() {
-        assert_eq!(calculate(5.0, '+', 3.0).unwrap(), 8.0);
-    }
-
-    #[test]
-    fn test_subtraction() {
-        assert_eq!(calculate(10.0, '-', 3.0).unwrap(), 7.0);
-    }
 
-    #[test]
-    fn test_multiplication() {
-        assert_eq!(calculate(5.0, '*', 3.0).unwrap(), 15.0);
-    }
+                say!(
+                    transcript,
+                    "{}",
+                    "  history           - Show calculation history".magenta()
+                );
+                say!(
+                    transcript,
+                    "{}",
+                    "  words [money] x   - Spell out a number (money: dollars and cents)".magenta()
+                );
+                say!(
+                    transcript,
+                    "{}",
+                    "  split b, r[, n]   - Tip, total and share (e.g., split 84.50, 18%, 4)"
+                        .magenta()
+                );
+                say!(
+                    transcript,
+                    "{}",
+                    "  tip(b, r[, n])    - The same on one line; 100 + 10% is 110, 18% * 100 is 18"
+                        .magenta()
+                );
+                say!(
+                    transcript,
+                    "{}",
+                    "  Finance           - pmt, fv, pv, nper, npv, irr (see 'help finance')"
+                        .magenta()
+                );
+                say!(
+                    transcript,
+                    "{}",
+                    "  amortize p r y    - Loan table (e.g., amortize 250000 5% 30 > loan.csv)"
+                        .magenta()
+                );
+                say!(
+                    transcript,
+                    "{}",
+                    "  VAT               - vat_add(100, 21%), vat_remove(121, 21%)".magenta()
+                );
+                say!(
+                    transcript,
+                    "{}",
+                    "  Percentages       - pctchange(80, 95) = 18.75, pctof(30, 120) = 25"
+                        .magenta()
+                );
+                say!(
+                    transcript,
+                    "{}",
+                    "  Statistics        - mean, median, stddev, variance (sample),".magenta()
+                );
+                say!(
+                    transcript,
+                    "{}",
+                    "                      pstddev, pvariance (population), min, max, sum"
+                        .magenta()
+                );
+                say!(
+                    transcript,
+                    "{}",
+                    "  linreg(xs, ys)    - Fit a line, sets slope, intercept and r2".magenta()
+                );
+                say!(
+                    transcript,
+                    "{}",
+                    "  Random            - rand(), randint(1, 6), randn(mu, sigma)".magenta()
+                );
+                say!(
+                    transcript,
+                    "{}",
+                    "  seed n            - Make random numbers reproducible".magenta()
+                );
+                say!(
+                    transcript,
+                    "{}",
+                    "  Distributions     - normcdf(x[, mu, sigma]), norminv(p[, mu, sigma]),"
+                        .magenta()
+                );
+                say!(
+                    transcript,
+                    "{}",
+                    "                      binompdf(n, p, k), poissonpdf(lambda, k)".magenta()
+                );
+                say!(
+                    transcript,
+                    "{}",
+                    "  Vectors           - [1, 2, 3] * 2, dot(a, b), cross(a, b), norm(a)"
+                        .magenta()
+                );
+                say!(
+                    transcript,
+                    "{}",
+                    "  Matrices          - [[1, 2], [3, 4]] * [[5], [6]], det, inv, transpose"
+                        .magenta()
+                );
+                say!(
+                    transcript,
+                    "{}",
+                    "                      linsolve(A, b) solves A * x = b".magenta()
+                );
+                say!(
+                    transcript,
+                    "{}",
+                    "  roots(1, -3, 2)   - Real roots of x^2 - 3x + 2 (coefficients, highest first)"
+                        .magenta()
+                );
+                say!(
+                    transcript,
+                    "{}",
+                    "  quadratic(a, b, c), cubic(a, b, c, d) - All roots and the discriminant"
+                        .magenta()
+                );
+                say!(
+                    transcript,
+                    "{}",
+                    "  diff(x^3, x, 2)   - Derivative of an expression in x at x = 2".magenta()
+                );
+                say!(
+                    transcript,
+                    "{}",
+                    "  integrate(x^2, x, 0, 3[, tol]) - Definite integral from 0 to 3".magenta()
+                );
+                say!(
+                    transcript,
+                    "{}",
+                    "  mcintegrate(x^2, x, 0, 3[, n]) - Monte Carlo estimate and its error"
+                        .magenta()
+                );
+                say!(
+                    transcript,
+                    "{}",
+                    "  solve(x^2 - 7, x[, guess | a, b]) - Numeric root of an equation".magenta()
+                );
+                say!(
+                    transcript,
+                    "{}",
+                    "  simplify <expr>   - Collect like terms: simplify 2*x + 3*x - x".magenta()
+                );
+                say!(
+                    transcript,
+                    "{}",
+                    "  fmt <expr>        - Reprint with minimal parentheses: fmt ((2+3))*4"
+                        .magenta()
+                );
+                say!(
+                    transcript,
+                    "{}",
+                    "  explain <expr>    - Evaluate step by step: explain 2 + 3 * (4 - 1)^2"
+                        .magenta()
+                );
+                say!(
+                    transcript,
+                    "{}",
+                    "  Math functions    - sin cos tan asin acos atan exp ln log sqrt abs floor ceil round"
+                        .magenta()
+                );
+                say!(transcript, "{}", "  Constants         - pi, e".magenta());
+                say!(
+                    transcript,
+                    "{}",
+                    "  divmod(17, 5)     - Quotient and remainder as a list: [3, 2]".magenta()
+                );
+                say!(
+                    transcript,
+                    "{}",
+                    "  cfrac(pi, 4)      - Continued fraction and convergents: 22/7, 355/113"
+                        .magenta()
+                );
+                say!(
+                    transcript,
+                    "{}",
+                    "  Angles            - 45°30'15\" or 45d30m15s, dms(x); set angles degrees"
+                        .magenta()
+                );
+                say!(
+                    transcript,
+                    "{}",
+                    "  Mixed numbers     - 3/4 + 1 1/2 = 2.25".magenta()
+                );
+                say!(
+                    transcript,
+                    "{}",
+                    "  plot(sin(x), x, -pi, pi) - Draw a graph (a table of values with --accessible)"
+                        .magenta()
+                );
+                say!(
+                    transcript,
+                    "{}",
+                    "  trend [n]         - Sparkline of the last n results (default 20)".magenta()
+                );
+                say!(
+                    transcript,
+                    "{}",
+                    "  repeat n [hist] expr - Run expr n times (e.g. with rand()) and summarise"
+                        .magenta()
+                );
+                say!(
+                    transcript,
+                    "{}",
+                    "  range(1, 10[, step]), map(x^2, x, list) - Build lists".magenta()
+                );
+                say!(
+                    transcript,
+                    "{}",
+                    "  fib(n), triangular(n), catalan(n) - Exact integer sequences".magenta()
+                );
+                say!(
+                    transcript,
+                    "{}",
+                    "  divisors(n), sigma(n), totient(n) - Divisor functions".magenta()
+                );
+                say!(
+                    transcript,
+                    "{}",
+                    "  hosts(/26), netmask(/20), inrange(ip, 10.0.0.0/8) - IPv4 networks".magenta()
+                );
+                say!(
+                    transcript,
+                    "{}",
+                    "  set [name value]  - Show or change settings (e.g., set vat 21%)".magenta()
+                );
+                say!(
+                    transcript,
+                    "{}",
+                    "  mode [rpn|algebraic] - Switch to reverse Polish input (3 4 + 5 *) and back"
+                        .magenta()
+                );
+                say!(
+                    transcript,
+                    "{}",
+                    "  →                 - Accept the dimmed hint from an earlier expression"
+                        .magenta()
+                );
+                say!(
+                    transcript,
+                    "{}",
+                    "  M+ / M- [expr]    - Add the last result (or expr) to memory, or subtract it"
+                        .magenta()
+                );
+                say!(
+                    transcript,
+                    "{}",
+                    "  MR / MC           - Recall memory (also inside expressions: MR * 2) / clear it"
+                        .magenta()
+                );
+                say!(
+                    transcript,
+                    "{}",
+                    "  data load f.csv   - Load a column: data load f.csv col=2 [as name]"
+                        .magenta()
+                );
+                say!(
+                    transcript,
+                    "{}",
+                    "  clear             - Clear history".magenta()
+                );
+                say!(
+                    transcript,
+                    "{}",
+                    "  save              - Save history to file".magenta()
+                );
+                say!(
+                    transcript,
+                    "{}",
+                    "  last              - Show last calculation".magenta()
+                );
+                say!(
+                    transcript,
+                    "{}",
+                    "  full              - Last result with every digit, and in hex-float form"
+                        .magenta()
+                );
+                say!(
+                    transcript,
+                    "{}",
+                    "  replay            - Recalculate the history and list changed results"
+                        .magenta()
+                );
+                say!(
+                    transcript,
+                    "{}",
+                    "  transcript <file> - Save the whole session so far, errors included"
+                        .magenta()
+                );
+                say!(
+                    transcript,
+                    "{}",
+                    "  show <name>       - Show a variable or constant and where it came from"
+                        .magenta()
+                );
+                say!(
+                    transcript,
+                    "{}",
+                    "  store <name>      - Keep the last result in a variable".magenta()
+                );
+                say!(
+                    transcript,
+                    "{}\n",
+                    "  exit/quit         - Exit calculator".magenta()
+                );
+            }
+            _ if rpn.is_some() => {
+                let stack = rpn.as_mut().expect("checked by the guard");
+                match rpn_command(stack, &input, &mut history) {
+                    Ok(text) => say!(transcript, "{}\n", text.green()),
+                    Err(e) => say!(transcript, "{} {}\n", "Error:".red(), e.red()),
+                }
+            }
+            _ => {
+                let input = match continuation(&input, &history) {
+                    Ok(full) => full.unwrap_or(input),
+                    Err(e) => {
+                        say!(transcript, "{} {}\n", "Error:".red(), e.red());
+                        continue;
+                    }
+                };
+                match evaluate_line(&input, &mut env) {
+                    Ok((expr, result)) => {
+                        let shown = result.show(&env.settings);
+                        if accessible {
+                            say!(
+                                transcript,
+                                "{} equals {}",
+                                expr.spoken(),
+                                spoken_number(&shown)
+                            );
+                        } else {
+                            say!(transcript, "{} {}\n", "=".green(), shown.green());
+                        }
+                        let record = HistoryEntry::new(expr.to_string(), result);
+                        history.push(record);
+                    }
+                    Err(e) => {
+                        say!(transcript, "{} {}\n", "Error:".red(), e.red());
+                        if let Some(path) = &env.settings.error_log
+                            && let Err(log_error) = log_error(path, &input, &e)
+                        {
+                            say!(
+                                transcript,
+                                "{} {}\n",
+                                "Warning:".yellow(),
+                                log_error.yellow()
+                            );
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    if let Some(path) = history_file
+        && let Err(e) = save_history(path, &history)
+    {
+        eprintln!("Warning: failed to save history: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn evaluate(input: &str) -> Result<Value, String> {
+        evaluate_expression(input, &mut Env::default())
+    }
+
+    #[test]
+    fn test_addition() {
+        assert_eq!(calculate(5.0, '+', 3.0).unwrap(), 8.0);
+    }
+
+    #[test]
+    fn test_subtraction() {
+        assert_eq!(calculate(10.0, '-', 3.0).unwrap(), 7.0);
+    }
+
+    #[test]
+    fn test_multiplication() {
+        assert_eq!(calculate(5.0, '*', 3.0).unwrap(), 15.0);
+    }
 
     #[test]
     fn test_division() {
@@ -333,6 +1823,250 @@ mod tests {
         assert_eq!(calculate(2.0, '^', 3.0).unwrap(), 8.0);
     }
 
+    #[test]
+    fn test_floor_division() {
+        assert_eq!(calculate(17.0, '\\', 5.0).unwrap(), 3.0);
+        assert_eq!(calculate(-7.0, '\\', 2.0).unwrap(), -4.0);
+        assert!(calculate(1.0, '\\', 0.0).is_err());
+    }
+
+    #[test]
+    fn test_store_command() {
+        let mut env = Env::default();
+        let history = vec![HistoryEntry::new("40 + 2".to_string(), Value::Number(42.0))];
+        assert_eq!(
+            store_command("subtotal", &history, &mut env).unwrap(),
+            "subtotal = 42"
+        );
+        let total = evaluate_expression("subtotal * 2", &mut env);
+        assert_eq!(total.unwrap(), Value::Number(84.0));
+        assert!(
+            show_command("subtotal", &env)
+                .unwrap()
+                .contains("`store subtotal`")
+        );
+        assert!(store_command("2x", &history, &mut env).is_err());
+        assert!(store_command("MR", &history, &mut env).is_err());
+        assert!(store_command("x", &[], &mut env).is_err());
+    }
+
+    #[test]
+    fn test_show_command() {
+        let mut env = Env::default();
+        evaluate_expression("linreg([1, 2, 3], [3, 5, 7])", &mut env).unwrap();
+        assert_eq!(
+            show_command("slope", &env).unwrap(),
+            "slope = 2\n  number, from `linreg()` in this session"
+        );
+        env.origins.get_mut("slope").unwrap().in_rc = true;
+        assert!(show_command("slope", &env).unwrap().ends_with("in .calcrc"));
+        assert_eq!(
+            show_command("e", &env).unwrap().lines().nth(1),
+            Some("  built-in constant")
+        );
+        env.memory = 3.0;
+        assert_eq!(
+            show_command("MR", &env).unwrap(),
+            "MR = 3\n  memory register (M+, M-, MC)"
+        );
+        assert!(show_command("nothing", &env).is_err());
+    }
+
+    #[test]
+    fn test_log_error() {
+        let path = std::env::temp_dir().join("calculator_test_errors.log");
+        let path = path.to_str().unwrap();
+        let _ = fs::remove_file(path);
+        log_error(path, "(2 + 3", "'(' at position 1 is never closed").unwrap();
+        log_error(path, "1/0", "Division by zero").unwrap();
+        let content = fs::read_to_string(path).unwrap();
+        let lines: Vec<Vec<&str>> = content.lines().map(|l| l.split('\t').collect()).collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(
+            lines[0][1..],
+            ["syntax", "(2 + 3", "'(' at position 1 is never closed"]
+        );
+        assert_eq!(lines[1][1..], ["evaluation", "1/0", "Division by zero"]);
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_replay_command() {
+        let mut env = Env::default();
+        env.variables
+            .insert("price".to_string(), Value::Number(10.0));
+        let history = vec![
+            HistoryEntry::new("2 + 2".to_string(), Value::Number(4.0)),
+            HistoryEntry::new("price*2".to_string(), Value::Number(20.0)),
+            HistoryEntry::new("rate + 1".to_string(), Value::Number(2.0)),
+        ];
+        env.variables.insert("rate".to_string(), Value::Number(1.0));
+        assert_eq!(
+            replay_command(&history, &env).unwrap(),
+            "All 3 result(s) unchanged"
+        );
+        env.variables
+            .insert("price".to_string(), Value::Number(12.5));
+        env.variables.remove("rate");
+        assert_eq!(
+            replay_command(&history, &env).unwrap(),
+            "2 of 3 result(s) changed:\n2. price*2: 20 -> 25\n3. rate + 1: 2 -> error: Unknown name: rate"
+        );
+        assert!(replay_command(&[], &env).is_err());
+
+        // RPN lines replay on a stack, not as algebraic input.
+        let history = vec![
+            HistoryEntry::new_rpn("3 4 +".to_string(), Value::Number(7.0)),
+            HistoryEntry::new_rpn("5 *".to_string(), Value::Number(35.0)),
+            HistoryEntry::new("2 + 2".to_string(), Value::Number(5.0)),
+        ];
+        assert_eq!(
+            replay_command(&history, &env).unwrap(),
+            "1 of 2 result(s) changed (1 RPN line(s) skipped, as they need values from \
+             earlier lines):\n3. 2 + 2: 5 -> 4"
+        );
+    }
+
+    #[test]
+    fn test_replay_leaves_session_alone() {
+        let mut env = Env::default();
+        env.rng.seed(1);
+        let history = vec![
+            HistoryEntry::new("rand()".to_string(), Value::Number(0.5)),
+            HistoryEntry::new("linreg([1, 2], [3, 5])".to_string(), Value::Number(0.0)),
+        ];
+        replay_command(&history, &env).unwrap();
+        assert!(!env.variables.contains_key("slope"));
+        let mut fresh = Env::default();
+        fresh.rng.seed(1);
+        assert_eq!(
+            evaluate_expression("rand()", &mut env).unwrap(),
+            evaluate_expression("rand()", &mut fresh).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_full_command() {
+        let history = vec![HistoryEntry::new("0.1".to_string(), Value::Number(0.1))];
+        assert_eq!(
+            full_command(&history).unwrap(),
+            "= 0.1000000000000000055511151231257827021181583404541015625\n  hex 0x1.999999999999ap-4"
+        );
+        assert!(full_command(&[]).is_err());
+    }
+
+    #[test]
+    fn test_repeat() {
+        let mut env = Env::default();
+        assert_eq!(
+            repeat_command("4 1 + 1", &mut env, false).unwrap(),
+            "4 runs: mean 2, stddev 0, min 2, max 2"
+        );
+        env.rng.seed(7);
+        let text = repeat_command("2000 hist randint(1, 6)", &mut env, false).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), 11);
+        assert!(lines[0].starts_with("2000 runs: mean 3.") && lines[0].ends_with("min 1, max 6"));
+        assert!(lines[1].starts_with("      1.0000  █"));
+        env.rng.seed(7);
+        let spoken = repeat_command("2000 hist randint(1, 6)", &mut env, true).unwrap();
+        assert!(spoken.lines().nth(1).unwrap().starts_with("from 1.0000: "));
+        assert!(!spoken.contains('█'));
+        assert!(repeat_command("1 rand()", &mut env, false).is_err());
+        assert!(repeat_command("10", &mut env, false).is_err());
+        assert!(repeat_command("10 \"a\"", &mut env, false).is_err());
+    }
+
+    #[test]
+    fn test_pace_and_fuel() {
+        assert_eq!(
+            evaluate("pace(42.195, \"3:45:00\")").unwrap().to_string(),
+            "0:05:20 per km, 0:08:35 per mile"
+        );
+        assert_eq!(
+            evaluate("pace(10, 50min)").unwrap().to_string(),
+            "0:05:00 per km, 0:08:03 per mile"
+        );
+        assert_eq!(evaluate("speed(30, 1:15:00)").unwrap(), Value::Number(24.0));
+        let round_trip = evaluate("l100km(mpg(6.5))").unwrap();
+        assert!(matches!(round_trip, Value::Number(n) if (n - 6.5).abs() < 1e-12));
+        assert!(evaluate("pace(10, 50)").is_err());
+        assert!(evaluate("speed(10, \"soon\")").is_err());
+        assert!(evaluate("mpg(0)").is_err());
+    }
+
+    #[test]
+    fn test_divisor_functions() {
+        assert_eq!(
+            evaluate("divisors(28)").unwrap().to_string(),
+            "[1, 2, 4, 7, 14, 28]"
+        );
+        assert_eq!(evaluate("sigma(28) - 28").unwrap().to_string(), "28");
+        assert_eq!(evaluate("totient(10)").unwrap().to_string(), "4");
+        assert!(evaluate("divisors(2.5)").is_err());
+        assert!(evaluate("sigma(0)").is_err());
+        assert!(evaluate("totient(10^13)").is_err());
+    }
+
+    #[test]
+    fn test_cfrac() {
+        assert_eq!(
+            evaluate("cfrac(pi, 4)").unwrap().to_string(),
+            "[3; 7, 15, 1]  convergents 3, 22/7, 333/106, 355/113"
+        );
+        assert_eq!(
+            evaluate("cfrac(1 3/8, 10)").unwrap().to_string(),
+            "[1; 2, 1, 2]  convergents 1, 3/2, 4/3, 11/8"
+        );
+        assert!(evaluate("cfrac(2, 0)").is_err());
+    }
+
+    #[test]
+    fn test_mixed_numbers() {
+        assert_eq!(evaluate("3/4 + 1 1/2").unwrap(), Value::Number(2.25));
+        assert_eq!(evaluate("2 * 1 1/2").unwrap(), Value::Number(3.0));
+        assert_eq!(evaluate("-1 1/4 + 2").unwrap(), Value::Number(0.75));
+        assert_eq!(evaluate("(1 1/2)").unwrap(), Value::Number(1.5));
+        assert!(evaluate("1 2").is_err());
+    }
+
+    #[test]
+    fn test_dms_angles() {
+        let mut env = Env::default();
+        let mut run = |input: &str| evaluate_expression(input, &mut env).unwrap();
+        assert_eq!(run("45°30' + 0"), Value::Number(45.5f64.to_radians()));
+        assert_eq!(run("dms(45d30m15s)").to_string(), "45°30'15\"");
+        assert_eq!(run("dms(-10°30')").to_string(), "-10°30'0\"");
+        env.settings.set_text("angles", "degrees").unwrap();
+        let mut run = |input: &str| evaluate_expression(input, &mut env).unwrap();
+        assert_eq!(run("45°30'15\" * 2"), Value::Number(91.00833333333334));
+        assert_eq!(run("dms(acos(0))").to_string(), "90°0'0\"");
+        assert_eq!(run("cos(180)"), Value::Number(-1.0));
+        assert_eq!(fmt_command("45°30' + 1").unwrap(), "45.5° + 1");
+        // Durations still mean time.
+        assert_eq!(evaluate("30m15s").unwrap(), Value::Duration(1815.0));
+    }
+
+    #[test]
+    fn test_divmod() {
+        assert_eq!(evaluate("divmod(17, 5)").unwrap().to_string(), "[3, 2]");
+        // Minutes and seconds in 200 seconds.
+        assert_eq!(evaluate("divmod(200, 60)").unwrap().to_string(), "[3, 20]");
+    }
+
+    #[test]
+    fn test_python_style_operators() {
+        assert_eq!(evaluate("2 ** 3 + 1").unwrap(), Value::Number(9.0));
+        assert_eq!(evaluate("2**3**2").unwrap(), evaluate("2^3^2").unwrap());
+        assert_eq!(evaluate("17 // 5 * 2").unwrap(), Value::Number(6.0));
+        assert_eq!(
+            evaluate("[7, 9] // 2").unwrap(),
+            Value::List(vec![3.0, 4.0])
+        );
+        assert_eq!(fmt_command("17//5").unwrap(), "17//5");
+        assert_eq!(fmt_command("2 ** 3").unwrap(), "2^3");
+    }
+
     #[test]
     fn test_modulo() {
         assert_eq!(calculate(10.0, '%', 3.0).unwrap(), 1.0);
@@ -345,79 +2079,857 @@ mod tests {
 
     #[test]
     fn test_parse_valid_input() {
-        let result = evaluate_expression("5 + 3").unwrap();
-        assert_eq!(result, 8.0);
+        let result = evaluate("5 + 3").unwrap();
+        assert_eq!(result, Value::Number(8.0));
     }
 
     #[test]
     fn test_parse_invalid_format() {
-        let result = evaluate_expression("5 +");
+        let result = evaluate("5 +");
         assert!(result.is_err());
     }
 
     #[test]
     fn test_parse_invalid_number() {
-        let result = evaluate_expression("abc + 3");
+        let result = evaluate("abc + 3");
         assert!(result.is_err());
     }
 
     #[test]
     fn test_history_entry_to_string() {
-        let entry = HistoryEntry::new("5 + 3".to_string(), 8.0);
+        let entry = HistoryEntry::new("5 + 3".to_string(), Value::Number(8.0));
         assert_eq!(entry.to_string(), "5 + 3 = 8");
-        assert_eq!(entry.result, 8.0)
+        assert_eq!(entry.result, Value::Number(8.0))
+    }
+
+    #[test]
+    fn test_history_entry_show() {
+        let mut env = Env::default();
+        let entry = HistoryEntry::new("16/2".to_string(), Value::Number(8.0));
+        set_command("mindecimals 2", &mut env).unwrap();
+        assert_eq!(entry.show(&env.settings), "16/2 = 8.00");
+        // The saved form keeps the plain value.
+        assert_eq!(entry.to_string(), "16/2 = 8");
+        assert_eq!(
+            set_command("mindecimals off", &mut env).unwrap(),
+            "mindecimals reset"
+        );
+        assert_eq!(entry.show(&env.settings), "16/2 = 8");
     }
 
     #[test]
     fn test_history_entry_new() {
-        let entry = HistoryEntry::new("5 + 3".to_string(), 8.0);
+        let entry = HistoryEntry::new("5 + 3".to_string(), Value::Number(8.0));
         assert_eq!(entry.expression, "5 + 3");
-        assert_eq!(entry.result, 8.0);
+        assert_eq!(entry.result, Value::Number(8.0));
     }
 
     #[test]
     fn test_history_entry_with_float_result() {
-        let entry = HistoryEntry::new("10 / 3".to_string(), 3.333333);
+        let entry = HistoryEntry::new("10 / 3".to_string(), Value::Number(3.333333));
         assert!(entry.to_string().contains("10 / 3 = "));
     }
 
     #[test]
     fn test_save_and_load_history() {
-        let mut history = Vec::new();
-        history.push(HistoryEntry::new("5 + 3".to_string(), 8.0));
-        history.push(HistoryEntry::new("10 * 2".to_string(), 20.0));
+        let history = vec![
+            HistoryEntry::new("5 + 3".to_string(), Value::Number(8.0)),
+            HistoryEntry::new("10 * 2".to_string(), Value::Number(20.0)),
+        ];
 
-        save_history(&history).unwrap();
-        let loaded = load_history();
+        let dir = std::env::temp_dir().join(format!("calc-history-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("history.txt");
+        let path = path.to_str().unwrap();
 
-        assert_eq!(loaded.len(), 2);
+        save_history(path, &history).unwrap();
+        let (loaded, skipped) = load_history(path).unwrap();
 
-        std::fs::remove_file("history.txt").ok();
+        assert_eq!((loaded.len(), skipped), (2, 0));
+        let text = std::fs::read_to_string(path).unwrap();
+        assert!(text.starts_with("# cll-calculator history v3\n5 + 3 = 8\n"));
+
+        // Every change backs up the old file, keeping the newest few.
+        for n in 0..HISTORY_BACKUPS + 2 {
+            std::fs::write(path, format!("{} = {}\n", n, n)).unwrap();
+            backup_history(path, &format!("2026010100000{}", n)).unwrap();
+        }
+        let mut backups: Vec<String> = std::fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .filter(|name| name.ends_with(".bak"))
+            .collect();
+        backups.sort();
+        assert_eq!(backups.len(), HISTORY_BACKUPS);
+        let newest = std::fs::read_to_string(dir.join(&backups[HISTORY_BACKUPS - 1])).unwrap();
+        assert_eq!(
+            newest,
+            format!("{} = {}\n", HISTORY_BACKUPS + 1, HISTORY_BACKUPS + 1)
+        );
+
+        std::fs::remove_dir_all(&dir).ok();
     }
 
     #[test]
     fn test_load_empty_history() {
-        std::fs::remove_file("history.txt").ok();
+        let (history, skipped) = load_history("no/such/history.txt").unwrap();
+        assert!(history.is_empty() && skipped == 0);
+    }
 
-        let history = load_history();
-        assert!(history.is_empty());
+    #[test]
+    fn test_history_migration() {
+        let old = ["5 + 3 = 8", "not an entry", "d = 2024-03-01"];
+        let entries = migrate_history(1, &old).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].result, Value::Number(8.0));
+        // RPN lines from before the tag are recognised by their operator.
+        let entries = migrate_history(2, &["3 4 + = 7", "rpn: 1 = 1"]).unwrap();
+        assert!(entries[0].rpn && !entries[1].rpn);
+        let entries = migrate_history(3, &["rpn: 3 4 + = 7", "2 + 5 = 7"]).unwrap();
+        assert_eq!(entries[0].expression, "3 4 +");
+        assert!(entries[0].rpn && !entries[1].rpn);
+        assert_eq!(entries[0].to_string(), "rpn: 3 4 + = 7");
+        assert!(migrate_history(HISTORY_VERSION + 1, &old).is_err());
     }
 
     #[test]
     fn test_parentheses() {
-        assert_eq!(evaluate_expression("(5 + 3) * 2").unwrap(), 16.0);
-        assert_eq!(evaluate_expression("2 * (3 + 4)").unwrap(), 14.0);
+        assert_eq!(evaluate("(5 + 3) * 2").unwrap(), Value::Number(16.0));
+        assert_eq!(evaluate("2 * (3 + 4)").unwrap(), Value::Number(14.0));
+    }
+
+    #[test]
+    fn test_unbalanced_brackets() {
+        assert_eq!(
+            evaluate("(2 + 3").unwrap_err(),
+            "'(' at position 1 is never closed"
+        );
+        assert_eq!(
+            evaluate("2 + 3)").unwrap_err(),
+            "')' at position 6 has no matching '('"
+        );
+        assert_eq!(
+            evaluate("((1 + 2) * 3").unwrap_err(),
+            "'(' at position 1 is never closed"
+        );
+        assert_eq!(
+            evaluate("[1, (2]").unwrap_err(),
+            "']' at position 7 does not match '(' at position 5"
+        );
+        assert!(check_brackets("unroman(\"X)\")").is_ok());
     }
 
     #[test]
     fn test_precedence_expression() {
-        assert_eq!(evaluate_expression("5 + 3 * 2").unwrap(), 11.0); // NOT 16!
-        assert_eq!(evaluate_expression("10 / 2 + 3").unwrap(), 8.0);
+        assert_eq!(evaluate("5 + 3 * 2").unwrap(), Value::Number(11.0)); // NOT 16!
+        assert_eq!(evaluate("10 / 2 + 3").unwrap(), Value::Number(8.0));
     }
 
     #[test]
     fn test_complex() {
-        assert_eq!(evaluate_expression("((2 + 3) * 4) - 1").unwrap(), 19.0);
-        assert_eq!(evaluate_expression("2 ^ 3 + 1").unwrap(), 9.0);
+        assert_eq!(evaluate("((2 + 3) * 4) - 1").unwrap(), Value::Number(19.0));
+        assert_eq!(evaluate("2 ^ 3 + 1").unwrap(), Value::Number(9.0));
+    }
+
+    #[test]
+    fn test_duration_expressions() {
+        assert_eq!(evaluate("1h30m + 45m").unwrap().to_string(), "2:15:00");
+        assert_eq!(evaluate("90min / 4").unwrap(), Value::Duration(1350.0));
+        assert_eq!(evaluate("-1:30:00 + 2h").unwrap(), Value::Duration(1800.0));
+        assert!(evaluate("1h + 5").is_err());
+        assert_eq!(evaluate("9s").unwrap(), Value::Number(3.0));
+        assert_eq!(evaluate("5min + 30sec").unwrap().to_string(), "0:05:30");
+        assert_eq!(evaluate("30sec + 5min").unwrap(), Value::Duration(330.0));
+        assert_eq!(evaluate("1h - 90sec").unwrap().to_string(), "0:58:30");
+        // `30s` is always the square root of 30, whatever it is added to.
+        assert_eq!(evaluate("9s + 1").unwrap(), Value::Number(4.0));
+        assert!(evaluate("5min + 30s").is_err());
+    }
+
+    #[test]
+    fn test_tokenize_names_and_dates() {
+        assert_eq!(
+            tokenize("epoch(2024-05-01 12:00)"),
+            vec!["epoch", "(", "2024-05-01 12:00", ")"]
+        );
+        assert_eq!(tokenize("2 - -3"), vec!["2", "-", "-3"]);
+        assert_eq!(tokenize("9s-1"), vec!["9", "s", "-", "1"]);
+    }
+
+    #[test]
+    fn test_timestamp_functions() {
+        assert_eq!(
+            evaluate("epoch(2024-05-01 12:00)").unwrap(),
+            Value::Number(1714564800.0)
+        );
+        assert_eq!(
+            evaluate("fromepoch(1714557600) + 90min")
+                .unwrap()
+                .to_string(),
+            "2024-05-01 11:30:00 UTC"
+        );
+        assert!(matches!(evaluate("now()").unwrap(), Value::Time(_)));
+        assert!(evaluate("epoch(1, 2)").is_err());
+        assert!(evaluate("epoch(2024-05-01").is_err());
+        assert!(evaluate("(5 + 3").is_err());
+    }
+
+    #[test]
+    fn test_roman_expressions() {
+        assert_eq!(evaluate("roman(2000 + 24)").unwrap().to_string(), "MMXXIV");
+        assert_eq!(
+            evaluate("unroman(\"MCMXCIX\") + 1").unwrap(),
+            Value::Number(2000.0)
+        );
+        assert!(evaluate("unroman(\"MCM").is_err());
+        assert_eq!(evaluate("unroman(MCMXCIX)").unwrap(), Value::Number(1999.0));
+        assert!(evaluate("unroman(ABC)").is_err());
+        assert!(evaluate("roman(5) + 1").is_err());
+    }
+
+    #[test]
+    fn test_base_conversion() {
+        assert_eq!(evaluate("tobase(255, 16)").unwrap().to_string(), "FF");
+        assert_eq!(
+            evaluate("frombase(\"z\", 36) + 1").unwrap(),
+            Value::Number(36.0)
+        );
+        assert_eq!(
+            evaluate("frombase(tobase(-42, 7), 7)").unwrap(),
+            Value::Number(-42.0)
+        );
+        assert_eq!(evaluate("tobase(0.1, 2)").unwrap().to_string(), "0.0(0011)");
+        assert_eq!(evaluate("tobase(10.5, 2)").unwrap().to_string(), "1010.1");
+        assert!(evaluate("tobase(10, 37)").is_err());
+        assert!(evaluate("frombase(\"19\", 8)").is_err());
+    }
+
+    #[test]
+    fn test_bit_functions() {
+        assert_eq!(evaluate("popcount(255)").unwrap(), Value::Number(8.0));
+        assert_eq!(evaluate("bitget(5, 2)").unwrap(), Value::Number(1.0));
+        assert_eq!(evaluate("bitset(5, 1)").unwrap(), Value::Number(7.0));
+        assert_eq!(evaluate("bitclear(5, 0)").unwrap(), Value::Number(4.0));
+        assert_eq!(evaluate("rotl(129, 1, 8)").unwrap(), Value::Number(3.0));
+        assert_eq!(evaluate("rotr(3, 1, 8)").unwrap(), Value::Number(129.0));
+        assert!(evaluate("bitget(1, 64)").is_err());
+        assert!(evaluate("rotl(1, 1, 0)").is_err());
+    }
+
+    #[test]
+    fn test_bit_functions_keep_every_digit() {
+        assert_eq!(
+            evaluate("rotr(3, 1)").unwrap().to_string(),
+            "9223372036854775809"
+        );
+        assert_eq!(
+            evaluate("bitset(0, 63)").unwrap().to_string(),
+            "9223372036854775808"
+        );
+        assert_eq!(
+            evaluate("bitset(0, 53)").unwrap(),
+            Value::Number(2f64.powi(53))
+        );
+        // Results past 2^53 can be passed back in exactly.
+        assert_eq!(
+            evaluate("popcount(bitset(0, 63))").unwrap(),
+            Value::Number(1.0)
+        );
+        assert_eq!(
+            evaluate("bitclear(bitset(1, 63), 63)").unwrap(),
+            Value::Number(1.0)
+        );
+    }
+
+    #[test]
+    fn test_bit_functions_follow_word_size() {
+        let mut env = Env::default();
+        env.settings.set("wordsize", 8.0).unwrap();
+        let popcount = evaluate_expression("popcount(-1)", &mut env);
+        assert_eq!(popcount.unwrap(), Value::Number(8.0));
+        let rotate = evaluate_expression("rotr(1, 1)", &mut env);
+        assert_eq!(rotate.unwrap(), Value::Number(128.0));
+    }
+
+    #[test]
+    fn test_words_command() {
+        assert_eq!(
+            words_command("20 + 1", &mut Env::default()).unwrap(),
+            "twenty-one"
+        );
+        assert_eq!(
+            words_command("money 1234.56", &mut Env::default()).unwrap(),
+            "one thousand two hundred thirty-four dollars and fifty-six cents"
+        );
+        assert!(words_command("1h", &mut Env::default()).is_err());
+    }
+
+    #[test]
+    fn test_percent_and_tip() {
+        assert_eq!(evaluate("18%").unwrap(), Value::Number(0.18));
+        assert_eq!(evaluate("10 % 3").unwrap(), Value::Number(1.0));
+        assert_eq!(
+            evaluate("tip(200, 15%)").unwrap().to_string(),
+            "Tip: 30.00, Total: 230.00, Per person: 230.00"
+        );
+        assert_eq!(
+            evaluate("tip(84.50, 18%, 4)").unwrap().to_string(),
+            "Tip: 15.21, Total: 99.71, Per person: 24.93"
+        );
+        assert!(evaluate("tip(84.50, 18%, 0)").is_err());
+    }
+
+    #[test]
+    fn test_dash_separated_numbers_stay_arithmetic() {
+        assert_eq!(evaluate("1000-10-20").unwrap(), Value::Number(970.0));
+        assert_eq!(evaluate("1234-56-78").unwrap(), Value::Number(1100.0));
+        assert!(matches!(evaluate("2024-05-01").unwrap(), Value::Time(_)));
+    }
+
+    #[test]
+    fn test_percent_change() {
+        assert_eq!(evaluate("100 + 10%").unwrap(), Value::Number(110.0));
+        assert_eq!(evaluate("80 - 25%").unwrap(), Value::Number(60.0));
+        assert_eq!(evaluate("200 * 15%").unwrap(), Value::Number(30.0));
+        assert_eq!(evaluate("max(1, 100 + 10%)").unwrap(), Value::Number(110.0));
+        assert_eq!(evaluate("1h + 50%").unwrap().to_string(), "1:30:00");
+        // A percentage followed by an operator is still a percentage.
+        assert_eq!(evaluate("18% * 100").unwrap(), Value::Number(18.0));
+        assert_eq!(evaluate("50% + 1").unwrap(), Value::Number(1.5));
+        assert_eq!(evaluate("10 % 3 + 1").unwrap(), Value::Number(2.0));
+        assert_eq!(evaluate("10 % -3").unwrap(), Value::Number(1.0));
+        let text = explain_command("100 + 10%", &mut Env::default()).unwrap();
+        assert_eq!(
+            text.lines().nth(1).unwrap().trim_end(),
+            "  = 110   add percent  100 + 10% = 110"
+        );
+        assert_eq!(simplify_command("x + 10%").unwrap(), "= 1.1*x");
+        assert_eq!(
+            split_command("84.50, 18%, 4", &mut Env::default()).unwrap(),
+            "Tip: 15.21\nTotal: 99.71\nPer person: 24.93"
+        );
+        assert!(split_command("84.50", &mut Env::default()).is_err());
+    }
+
+    #[test]
+    fn test_vat_functions() {
+        let mut env = Env::default();
+        assert_eq!(
+            evaluate_expression("vat_add(100, 21%)", &mut env).unwrap(),
+            Value::Number(121.0)
+        );
+        // Rates are fractions, the same as for tip() and amortize.
+        assert_eq!(evaluate("vat_add(100, 0.5)").unwrap(), Value::Number(150.0));
+        assert_eq!(evaluate("vat_add(100, 1)").unwrap(), Value::Number(200.0));
+        assert_eq!(
+            evaluate("vat_add(100, 0.5%)").unwrap(),
+            Value::Number(100.5)
+        );
+        // A bare 21 is rejected rather than read as 2100% or 0.21%.
+        assert!(evaluate("vat_add(100, 21)").is_err());
+        assert!(set_command("vat 21", &mut env).is_err());
+        assert!(evaluate_expression("vat_remove(121)", &mut env).is_err());
+        set_command("vat 21%", &mut env).unwrap();
+        assert_eq!(
+            evaluate_expression("vat_remove(121)", &mut env).unwrap(),
+            Value::Number(100.0)
+        );
+        assert!(set_command("vat", &mut env).is_err());
+    }
+
+    #[test]
+    fn test_statistics_functions() {
+        assert_eq!(evaluate("mean(3, 7, 8, 12)").unwrap(), Value::Number(7.5));
+        assert_eq!(
+            evaluate("median(3, 7, 8, 12) + max(1, 2)").unwrap(),
+            Value::Number(9.5)
+        );
+        assert_eq!(
+            evaluate("pvariance(3, 7, 8, 12)").unwrap(),
+            Value::Number(10.25)
+        );
+        assert!(evaluate("stddev(4)").is_err());
+        assert!(evaluate("mean()").is_err());
+    }
+
+    #[test]
+    fn test_list_literals_and_linreg() {
+        assert_eq!(
+            evaluate("[1, -2, 3 * 2]").unwrap(),
+            Value::List(vec![1.0, -2.0, 6.0])
+        );
+        assert_eq!(evaluate("mean([1, 2], 6)").unwrap(), Value::Number(3.0));
+        assert_eq!(evaluate("[]").unwrap(), Value::List(vec![]));
+        assert!(evaluate("[1, 2").is_err());
+        assert!(evaluate("(1, 2]").is_err());
+        assert!(evaluate("[1, 2)").is_err());
+
+        let mut env = Env::default();
+        evaluate_expression("linreg([1, 2, 3], [3, 5, 7])", &mut env).unwrap();
+        assert_eq!(
+            evaluate_expression("slope * 10 + intercept", &mut env).unwrap(),
+            Value::Number(21.0)
+        );
+    }
+
+    #[test]
+    fn test_vector_expressions() {
+        assert_eq!(
+            evaluate("dot([1, 2, 3], [4, 5, 6])").unwrap(),
+            Value::Number(32.0)
+        );
+        assert_eq!(
+            evaluate("cross([1, 0, 0], [0, 1, 0])").unwrap().to_string(),
+            "[0, 0, 1]"
+        );
+        assert_eq!(evaluate("norm([3, 4])").unwrap(), Value::Number(5.0));
+        assert_eq!(
+            evaluate("[1, 2] * 3 + [1, 1]").unwrap().to_string(),
+            "[4, 7]"
+        );
+        assert!(evaluate("[1, 2] + [1, 2, 3]").is_err());
+    }
+
+    #[test]
+    fn test_negation() {
+        assert_eq!(evaluate("-(2 + 3)").unwrap(), Value::Number(-5.0));
+        assert_eq!(evaluate("4 * - 2").unwrap(), Value::Number(-8.0));
+        assert_eq!(evaluate("-[1, 2]").unwrap(), Value::List(vec![-1.0, -2.0]));
+        assert_eq!(evaluate("-2^2").unwrap(), Value::Number(4.0));
+    }
+
+    #[test]
+    fn test_expression_display_round_trips() {
+        for input in [
+            "2*x + 3*x - x",
+            "(1 + 2)*3",
+            "a - (b - c)",
+            "2^(3^2)",
+            "-(x + 1)",
+            "(50%)*2",
+            "(x + 1) s",
+            "f(x, [1, 2], \"text\")",
+            "2024-05-01 12:00:00 + 1:30:00",
+            "hosts(192.168.1.0/24)",
+        ] {
+            let expr = parse_expression(input).unwrap();
+            assert_eq!(expr.to_string(), input);
+            assert_eq!(parse_expression(&expr.to_string()).unwrap(), expr);
+        }
+    }
+
+    #[test]
+    fn test_constants_and_math_functions() {
+        assert_eq!(evaluate("cos(pi)").unwrap(), Value::Number(-1.0));
+        assert_eq!(evaluate("ln(e^2)").unwrap(), Value::Number(2.0));
+        let mut env = Env::default();
+        env.variables.insert("e".into(), Value::Number(5.0));
+        assert_eq!(
+            evaluate_expression("e + 1", &mut env).unwrap(),
+            Value::Number(6.0)
+        );
+    }
+
+    #[test]
+    fn test_trend_command() {
+        let history: Vec<HistoryEntry> = [1.0, 4.0, 2.0, 8.0]
+            .into_iter()
+            .map(|n| HistoryEntry::new(n.to_string(), Value::Number(n)))
+            .chain([HistoryEntry::new("x".into(), Value::Text("MMXXIV".into()))])
+            .collect();
+        assert_eq!(
+            trend_command("", &history, false).unwrap(),
+            "▁▄▂█  last 4 results: 1 -> 8 (min 1, max 8)"
+        );
+        assert!(
+            trend_command("3", &history, false)
+                .unwrap()
+                .starts_with("▃▁█  last 3")
+        );
+        assert_eq!(
+            trend_command("", &history, true).unwrap(),
+            "last 4 results: 1 -> 8 (min 1, max 8)"
+        );
+        assert!(trend_command("1", &history, false).is_err());
+        assert!(trend_command("", &history[..1], false).is_err());
+    }
+
+    #[test]
+    fn test_plot_command() {
+        let text = plot_command("plot(sin(x), x, -pi, pi)", &mut Env::default(), false).unwrap();
+        assert_eq!(text.lines().count(), 17);
+        assert!(text.contains('*'));
+        let spoken = plot_command("plot(x^2, x, 0, 10)", &mut Env::default(), true).unwrap();
+        assert_eq!(spoken.lines().nth(1), Some("at x = 1, 1"));
+        assert!(plot_command("plot(x, x)", &mut Env::default(), false).is_err());
+        assert!(plot_command("plot(x, x, 0, 1) + 1", &mut Env::default(), false).is_err());
+        assert!(evaluate("2 * plot(x, x, 0, 1)").is_err());
+    }
+
+    #[test]
+    fn test_explain_command() {
+        let text = explain_command("2 + 3 * (4 - 1)^2", &mut Env::default()).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines[0], "2 + 3*(4 - 1)^2");
+        assert_eq!(lines[1], "  = 2 + 3*3^2   subtract     4 - 1 = 3");
+        assert_eq!(lines.len(), 5);
+        assert!(lines[4].starts_with("  = 29 "));
+        assert!(explain_command("1 / 0", &mut Env::default()).is_err());
+    }
+
+    #[test]
+    fn test_simplify_command() {
+        assert_eq!(simplify_command("2*x + 3*x - x").unwrap(), "= 4*x");
+        assert_eq!(simplify_command("(x + 1)^2 - 1").unwrap(), "= x^2 + 2*x");
+        assert_eq!(simplify_command("(x - y)*(x + y)").unwrap(), "= x^2 - y^2");
+        assert_eq!(simplify_command("x - 2*x").unwrap(), "= -x");
+        assert_eq!(simplify_command("2 * 3 + 1").unwrap(), "= 7");
+        assert!(simplify_command("2 +").is_err());
+    }
+
+    #[test]
+    fn test_diff() {
+        assert_eq!(evaluate("diff(x^3, x, 2)").unwrap(), Value::Number(12.0));
+        assert_eq!(
+            evaluate("diff(1 / t, t, -2)").unwrap(),
+            Value::Number(-0.25)
+        );
+        assert!(evaluate("diff(x^2, 3, 1)").is_err());
+        assert!(evaluate("diff(x^2, x)").is_err());
+        assert!(evaluate("diff(y * 2, x, 1)").is_err());
+        assert!(evaluate("diff(1 / x, x, 0)").is_err());
+    }
+
+    #[test]
+    fn test_integrate() {
+        assert_eq!(
+            evaluate("integrate(x^2, x, 0, 3)").unwrap(),
+            Value::Number(9.0)
+        );
+        assert_eq!(
+            evaluate("integrate(4 / (1 + t^2), t, 0, 1)")
+                .unwrap()
+                .to_string(),
+            "3.14159265359"
+        );
+        match evaluate("integrate(4 / (1 + t^2), t, 0, 1, 0.001)").unwrap() {
+            Value::Number(rough) => assert!((rough - std::f64::consts::PI).abs() < 0.001),
+            other => panic!("expected a number, got {:?}", other),
+        }
+        assert!(evaluate("integrate(x, x, 0, 1, 0)").is_err());
+        assert!(evaluate("integrate(1 / x, x, 0, 1)").is_err());
+        assert!(evaluate("integrate(x, x, 0)").is_err());
+    }
+
+    #[test]
+    fn test_mcintegrate() {
+        let mut env = Env::default();
+        env.rng.seed(3);
+        let Value::List(result) =
+            evaluate_expression("mcintegrate(x^2, x, 0, 3, 100000)", &mut env).unwrap()
+        else {
+            panic!("expected an estimate and its error");
+        };
+        assert!(result[1] > 0.0 && result[1] < 0.05);
+        assert!((result[0] - 9.0).abs() < 4.0 * result[1]);
+        assert_eq!(
+            evaluate("mcintegrate(2, t, 1, 4)").unwrap(),
+            Value::List(vec![6.0, 0.0])
+        );
+        assert!(evaluate("mcintegrate(x, x, 0, 1, 1)").is_err());
+        assert!(evaluate("mcintegrate(x, x, 0, 1, 2.5)").is_err());
+        assert!(evaluate("mcintegrate(x, x, 0)").is_err());
+    }
+
+    #[test]
+    fn test_quadratic_and_cubic() {
+        let solve = |input| evaluate(input).unwrap().to_string();
+        assert_eq!(solve("quadratic(1, -3, 2)"), "x = 1, 2  (discriminant 1)");
+        assert_eq!(solve("quadratic(1, 2, 1)"), "x = -1, -1  (discriminant 0)");
+        assert_eq!(
+            solve("quadratic(1, 2, 5)"),
+            "x = -1 ± 2i  (discriminant -16)"
+        );
+        assert_eq!(solve("quadratic(1, 0, 1)"), "x = ±i  (discriminant -4)");
+        assert_eq!(
+            solve("cubic(1, -6, 11, -6)"),
+            "x = 1, 2, 3  (discriminant 4)"
+        );
+        assert_eq!(
+            solve("cubic(1, 0, 0, -8)"),
+            "x = 2, -1 ± 1.73205080757i  (discriminant -1728)"
+        );
+        assert!(evaluate("quadratic(0, 1, 2)").is_err());
+        assert!(evaluate("cubic(1, 2, 3)").is_err());
+    }
+
+    #[test]
+    fn test_solve() {
+        let root = |input| match evaluate(input).unwrap() {
+            Value::Number(n) => n,
+            other => panic!("expected a number, got {:?}", other),
+        };
+        assert!((root("solve(x^2 - 7, x)") - 7f64.sqrt()).abs() < 1e-12);
+        assert!((root("solve(x^2 - 7, x, -1)") + 7f64.sqrt()).abs() < 1e-12);
+        assert!((root("solve(x^3 - x - 2, x, 1, 2)") - 1.521_379_706_804_567_6).abs() < 1e-12);
+        assert!(evaluate("solve(x^2 - 7, x, 3, 4)").is_err());
+        assert!(evaluate("solve(x^2 + 1, x)").is_err());
+        assert!(evaluate("solve(x^2, 2)").is_err());
+    }
+
+    #[test]
+    fn test_range_and_map() {
+        assert_eq!(
+            evaluate("map(x^2, x, range(1, 5))").unwrap().to_string(),
+            "[1, 4, 9, 16, 25]"
+        );
+        assert_eq!(
+            evaluate("range(10, 1, -4)").unwrap().to_string(),
+            "[10, 6, 2]"
+        );
+        assert_eq!(
+            evaluate("range(0, 1, 0.25)").unwrap().to_string(),
+            "[0, 0.25, 0.5, 0.75, 1]"
+        );
+        assert_eq!(
+            evaluate("mean(map(2 * n, n, range(1, 3)))").unwrap(),
+            Value::Number(4.0)
+        );
+        assert!(evaluate("range(1, 5, -1)").is_err());
+        assert!(evaluate("range(1, 5, 0)").is_err());
+        assert!(evaluate("map(x, x, 5)").is_err());
+        assert!(evaluate("map(1 / x, x, [1, 0])").is_err());
+    }
+
+    #[test]
+    fn test_integer_sequences() {
+        assert_eq!(evaluate("fib(10) + 1").unwrap(), Value::Number(56.0));
+        assert_eq!(evaluate("triangular(100)").unwrap(), Value::Number(5050.0));
+        assert_eq!(
+            evaluate("triangular(100000)").unwrap(),
+            Value::Number(5_000_050_000.0)
+        );
+        assert!(evaluate("triangular(6074001000)").is_err());
+        assert_eq!(evaluate("catalan(10)").unwrap(), Value::Number(16796.0));
+        assert_eq!(
+            evaluate("fib(100)").unwrap().to_string(),
+            "354224848179261915075"
+        );
+        // Big results can still be used, as ordinary rounded numbers.
+        assert_eq!(
+            evaluate("fib(100) + 1").unwrap(),
+            Value::Number(354224848179261915075.0)
+        );
+        assert!(evaluate("fib(-1)").is_err());
+        assert!(evaluate("catalan(2.5)").is_err());
+    }
+
+    #[test]
+    fn test_fmt_command() {
+        assert_eq!(
+            fmt_command("((2+3))*4 -(5-1)").unwrap(),
+            "(2 + 3)*4 - (5 - 1)"
+        );
+        assert_eq!(fmt_command("a-(b+c)").unwrap(), "a - (b + c)");
+        assert_eq!(fmt_command("2 ^ (3 ^ 2)").unwrap(), "2^(3^2)");
+        assert!(fmt_command("2 +").is_err());
+        let (expr, result) = evaluate_line("(1+2) * 3", &mut Env::default()).unwrap();
+        assert_eq!(expr.to_string(), "(1 + 2)*3");
+        assert_eq!(result, Value::Number(9.0));
+    }
+
+    #[test]
+    fn test_spoken_expressions() {
+        let spoken = |input| parse_expression(input).unwrap().spoken();
+        assert_eq!(spoken("5 + 3"), "5 plus 3");
+        assert_eq!(
+            spoken("(2 + 3) * -4"),
+            "open bracket 2 plus 3 close bracket times minus 4"
+        );
+        assert_eq!(
+            spoken("2^10 // 3"),
+            "2 to the power of 10 floor divided by 3"
+        );
+        assert_eq!(spoken("200 * 15%"), "200 times 15 percent");
+        assert_eq!(spoken("max(1, x)"), "max of 1, x");
+        assert_eq!(spoken_number("-2.5"), "minus 2.5");
+    }
+
+    #[test]
+    fn test_rpn_command() {
+        let mut stack = rpn::Stack::default();
+        let mut history = vec![];
+        let shown = rpn_command(&mut stack, "3 4 + 5", &mut history).unwrap();
+        assert_eq!(shown, "2: 7\n1: 5");
+        rpn_command(&mut stack, "*", &mut history).unwrap();
+        rpn_command(&mut stack, "dup", &mut history).unwrap();
+        let lines: Vec<String> = history.iter().map(|entry| entry.to_string()).collect();
+        assert_eq!(lines, ["rpn: * = 35"]);
+    }
+
+    #[test]
+    fn test_continuation() {
+        let history = vec![HistoryEntry::new("40 + 2".to_string(), Value::Number(42.0))];
+        let full = |input| continuation(input, &history).unwrap();
+        assert_eq!(full("* 1.21").as_deref(), Some("42 * 1.21"));
+        assert_eq!(full("+5").as_deref(), Some("42 +5"));
+        assert_eq!(full("- 5").as_deref(), Some("42 - 5"));
+        assert_eq!(full("/ 2").as_deref(), Some("42 / 2"));
+        // Without a space these keep their own meaning.
+        assert_eq!(full("-5"), None);
+        assert_eq!(full("-(2 + 3)"), None);
+        assert_eq!(full("/24"), None);
+        assert_eq!(full("5 + 3"), None);
+        assert!(continuation("+ 1", &[]).is_err());
+        let text = vec![HistoryEntry::new(
+            "roman(4)".to_string(),
+            Value::Text("IV".into()),
+        )];
+        assert!(continuation("+ 1", &text).is_err());
+    }
+
+    #[test]
+    fn test_memory_register() {
+        let mut env = Env::default();
+        let history = vec![HistoryEntry::new("2 + 3".to_string(), Value::Number(5.0))];
+        assert_eq!(
+            memory_command('+', "", &history, &mut env).unwrap(),
+            "M = 5"
+        );
+        assert_eq!(
+            memory_command('+', " 10", &history, &mut env).unwrap(),
+            "M = 15"
+        );
+        assert_eq!(
+            memory_command('-', " 1", &history, &mut env).unwrap(),
+            "M = 14"
+        );
+        let doubled = evaluate_expression("MR * 2", &mut env);
+        assert_eq!(doubled.unwrap(), Value::Number(28.0));
+        // The register does not clash with a variable of the same name.
+        env.variables.insert("MR".to_string(), Value::Number(1.0));
+        assert_eq!(
+            evaluate_expression("MR", &mut env).unwrap(),
+            Value::Number(14.0)
+        );
+        assert!(memory_command('+', "", &[], &mut env).is_err());
+        assert!(memory_command('+', " [1, 2]", &history, &mut env).is_err());
+    }
+
+    #[test]
+    fn test_networks() {
+        assert_eq!(evaluate("hosts(/26)").unwrap(), Value::Number(62.0));
+        assert_eq!(
+            evaluate("netmask(/20)").unwrap().to_string(),
+            "255.255.240.0"
+        );
+        assert_eq!(
+            evaluate("inrange(192.168.1.77, 192.168.1.0/24)").unwrap(),
+            Value::Number(1.0)
+        );
+        assert_eq!(
+            evaluate("inrange(192.168.2.1, 192.168.1.0/24)").unwrap(),
+            Value::Number(0.0)
+        );
+        // A slash after an operand is still division.
+        assert_eq!(evaluate("10 /2").unwrap(), Value::Number(5.0));
+        assert_eq!(evaluate("(8)/4").unwrap(), Value::Number(2.0));
+        assert!(evaluate("hosts(24)").is_err());
+    }
+
+    #[test]
+    fn test_polynomial_roots() {
+        assert_eq!(evaluate("roots(1, -3, 2)").unwrap().to_string(), "[1, 2]");
+        assert_eq!(
+            evaluate("roots([1, -6, 11, -6])").unwrap().to_string(),
+            "[1, 2, 3]"
+        );
+        assert_eq!(evaluate("roots(1, 0, 1)").unwrap().to_string(), "[]");
+        assert!(evaluate("roots(4)").is_err());
+    }
+
+    #[test]
+    fn test_matrix_expressions() {
+        assert_eq!(
+            evaluate("[[1,2],[3,4]] * [[5],[6]]").unwrap().to_string(),
+            "[[17], [39]]"
+        );
+        assert_eq!(
+            evaluate("det([[1, 2], [3, 4]])").unwrap(),
+            Value::Number(-2.0)
+        );
+        assert_eq!(
+            evaluate("inv([[2, 0], [0, 4]])").unwrap().to_string(),
+            "[[0.5, 0], [0, 0.25]]"
+        );
+        assert_eq!(
+            evaluate("transpose([[1, 2, 3]])").unwrap().to_string(),
+            "[[1], [2], [3]]"
+        );
+        assert!(evaluate("[[1, 2], [3]]").is_err());
+        assert!(evaluate("[[1, 2], 3]").is_err());
+        assert!(evaluate("inv([[1, 2], [2, 4]])").is_err());
+        assert_eq!(
+            evaluate("linsolve([[1, 1], [1, -1]], [10, 2])")
+                .unwrap()
+                .to_string(),
+            "[6, 4]"
+        );
+        assert!(evaluate("linsolve([[1, 1], [2, 2]], [1, 3])").is_err());
+    }
+
+    #[test]
+    fn test_data_command() {
+        let path = "test_data_command.csv";
+        std::fs::write(path, "x,y\n1,10\n2,20\n3,30\n").unwrap();
+        let mut env = Env::default();
+
+        let message = data_command(&format!("load {} col=2", path), &mut env).unwrap();
+        assert_eq!(message, "Loaded 3 values into 'data'");
+        assert_eq!(
+            evaluate_expression("mean(data)", &mut env).unwrap(),
+            Value::Number(20.0)
+        );
+
+        data_command(&format!("load {} col=x as xs", path), &mut env).unwrap();
+        assert_eq!(
+            evaluate_expression("max(xs) + min(data)", &mut env).unwrap(),
+            Value::Number(13.0)
+        );
+        assert_eq!(
+            evaluate_expression("linreg(xs, data)", &mut env).unwrap(),
+            Value::Text("slope = 10, intercept = 0, r2 = 1".into())
+        );
+        std::fs::write(path, "x,y\n1,10\n2,\n3,30\n").unwrap();
+        assert!(data_command(&format!("load {} col=y", path), &mut env).is_err());
+        assert!(data_command("load", &mut env).is_err());
+        assert!(data_command(&format!("load {} as 1x", path), &mut env).is_err());
+        std::fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn test_finance_functions() {
+        let Value::Number(payment) = evaluate("pmt(0.05/12, 360, 250000)").unwrap() else {
+            panic!("pmt() should return a number");
+        };
+        assert!((payment + 1342.05).abs() < 0.01);
+        assert_eq!(evaluate("fv(0, 12, -100)").unwrap(), Value::Number(1200.0));
+        assert!(evaluate("npv(0.1)").is_err());
+        assert!(evaluate("irr(-100, 110)").is_ok());
+    }
+
+    #[test]
+    fn test_amortize_command() {
+        let table = amortize_command("1200 0% 1", &mut Env::default()).unwrap();
+        assert_eq!(table.lines().count(), 13);
+        assert!(amortize_command("1200 0%", &mut Env::default()).is_err());
+
+        let path = "test_amortize.csv";
+        let message =
+            amortize_command(&format!("10000 6% 1 > {}", path), &mut Env::default()).unwrap();
+        assert!(message.contains("12 payments"));
+        let csv = std::fs::read_to_string(path).unwrap();
+        assert!(csv.starts_with("period,payment,interest,principal,balance\n1,860.66,50.00,"));
+        std::fs::remove_file(path).ok();
     }
 }
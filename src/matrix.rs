@@ -0,0 +1,303 @@
+use core::fmt;
+
+/// A dense matrix of numbers, stored row by row.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Matrix {
+    pub rows: usize,
+    pub cols: usize,
+    data: Vec<f64>,
+}
+
+impl Matrix {
+    pub fn from_rows(rows: Vec<Vec<f64>>) -> Result<Matrix, String> {
+        let cols = rows.first().map_or(0, Vec::len);
+        if cols == 0 {
+            return Err("A matrix needs at least one row and one column".to_string());
+        }
+        if rows.iter().any(|row| row.len() != cols) {
+            return Err("All matrix rows must have the same length".to_string());
+        }
+        Ok(Matrix {
+            rows: rows.len(),
+            cols,
+            data: rows.into_iter().flatten().collect(),
+        })
+    }
+
+    fn filled(rows: usize, cols: usize, f: impl Fn(usize, usize) -> f64) -> Matrix {
+        let data = (0..rows * cols).map(|i| f(i / cols, i % cols)).collect();
+        Matrix { rows, cols, data }
+    }
+
+    pub fn get(&self, row: usize, col: usize) -> f64 {
+        self.data[row * self.cols + col]
+    }
+
+    pub fn row(&self, row: usize) -> &[f64] {
+        &self.data[row * self.cols..(row + 1) * self.cols]
+    }
+
+    fn shape(&self) -> String {
+        format!("{}x{}", self.rows, self.cols)
+    }
+
+    pub fn transpose(&self) -> Matrix {
+        Matrix::filled(self.cols, self.rows, |i, j| self.get(j, i))
+    }
+
+    pub fn map(&self, f: impl Fn(f64) -> f64) -> Matrix {
+        Matrix {
+            rows: self.rows,
+            cols: self.cols,
+            data: self.data.iter().map(|&x| f(x)).collect(),
+        }
+    }
+
+    /// Element-wise `+` or `-` of two matrices of the same shape.
+    pub fn combine(&self, other: &Matrix, op: char) -> Result<Matrix, String> {
+        if (self.rows, self.cols) != (other.rows, other.cols) {
+            return Err(format!(
+                "Cannot apply '{}' to {} and {} matrices",
+                op,
+                self.shape(),
+                other.shape()
+            ));
+        }
+        let sign = if op == '-' { -1.0 } else { 1.0 };
+        Ok(Matrix::filled(self.rows, self.cols, |i, j| {
+            self.get(i, j) + sign * other.get(i, j)
+        }))
+    }
+
+    pub fn mul(&self, other: &Matrix) -> Result<Matrix, String> {
+        if self.cols != other.rows {
+            return Err(format!(
+                "Cannot multiply {} by {} matrix",
+                self.shape(),
+                other.shape()
+            ));
+        }
+        Ok(Matrix::filled(self.rows, other.cols, |i, j| {
+            (0..self.cols)
+                .map(|k| self.get(i, k) * other.get(k, j))
+                .sum()
+        }))
+    }
+
+    fn require_square(&self, what: &str) -> Result<(), String> {
+        if self.rows == self.cols {
+            Ok(())
+        } else {
+            Err(format!(
+                "{} needs a square matrix, got {}",
+                what,
+                self.shape()
+            ))
+        }
+    }
+
+    /// Determinant by elimination with partial pivoting.
+    pub fn det(&self) -> Result<f64, String> {
+        self.require_square("det()")?;
+        let n = self.rows;
+        let mut m = self.data.clone();
+        let mut det = 1.0;
+        for col in 0..n {
+            let pivot = (col..n)
+                .max_by(|&a, &b| m[a * n + col].abs().total_cmp(&m[b * n + col].abs()))
+                .unwrap();
+            if m[pivot * n + col] == 0.0 {
+                return Ok(0.0);
+            }
+            if pivot != col {
+                for k in 0..n {
+                    m.swap(pivot * n + k, col * n + k);
+                }
+                det = -det;
+            }
+            det *= m[col * n + col];
+            for row in col + 1..n {
+                let factor = m[row * n + col] / m[col * n + col];
+                for k in col..n {
+                    m[row * n + k] -= factor * m[col * n + k];
+                }
+            }
+        }
+        Ok(det)
+    }
+
+    /// Inverse by Gauss-Jordan elimination with partial pivoting.
+    pub fn inverse(&self) -> Result<Matrix, String> {
+        self.require_square("inv()")?;
+        let n = self.rows;
+        let mut a = self.clone();
+        let mut inv = Matrix::filled(n, n, |i, j| if i == j { 1.0 } else { 0.0 });
+        let scale = self.data.iter().fold(0.0, |acc: f64, x| acc.max(x.abs()));
+
+        for col in 0..n {
+            let pivot = (col..n)
+                .max_by(|&x, &y| a.get(x, col).abs().total_cmp(&a.get(y, col).abs()))
+                .unwrap();
+            if a.get(pivot, col).abs() <= scale * 1e-12 {
+                return Err("Matrix is singular, it has no inverse".to_string());
+            }
+            a.swap_rows(pivot, col);
+            inv.swap_rows(pivot, col);
+
+            let p = a.get(col, col);
+            for k in 0..n {
+                a.data[col * n + k] /= p;
+                inv.data[col * n + k] /= p;
+            }
+            for row in (0..n).filter(|&r| r != col) {
+                let factor = a.get(row, col);
+                for k in 0..n {
+                    a.data[row * n + k] -= factor * a.get(col, k);
+                    inv.data[row * n + k] -= factor * inv.get(col, k);
+                }
+            }
+        }
+        Ok(inv)
+    }
+
+    /// Solves `self * x = b` by Gaussian elimination with partial pivoting.
+    pub fn solve(&self, b: &[f64]) -> Result<Vec<f64>, String> {
+        self.require_square("linsolve()")?;
+        let n = self.rows;
+        if b.len() != n {
+            return Err(format!(
+                "linsolve() needs {} right-hand side values for a {} matrix, got {}",
+                n,
+                self.shape(),
+                b.len()
+            ));
+        }
+        let mut aug = Matrix::filled(n, n + 1, |i, j| if j < n { self.get(i, j) } else { b[i] });
+        let scale = aug.data.iter().fold(0.0, |acc: f64, x| acc.max(x.abs()));
+        let tolerance = scale * 1e-12;
+
+        let mut row = 0;
+        for col in 0..n {
+            let pivot = (row..n)
+                .max_by(|&x, &y| aug.get(x, col).abs().total_cmp(&aug.get(y, col).abs()))
+                .unwrap();
+            if aug.get(pivot, col).abs() <= tolerance {
+                continue;
+            }
+            aug.swap_rows(pivot, row);
+            for r in row + 1..n {
+                let factor = aug.get(r, col) / aug.get(row, col);
+                for k in col..=n {
+                    aug.data[r * (n + 1) + k] -= factor * aug.get(row, k);
+                }
+            }
+            row += 1;
+        }
+
+        if row < n {
+            // The leftover rows are all zero on the left; the system is
+            // consistent only if they are zero on the right as well.
+            return if (row..n).any(|r| aug.get(r, n).abs() > tolerance) {
+                Err("The system is singular and has no solution".to_string())
+            } else {
+                Err("The system is singular and has infinitely many solutions".to_string())
+            };
+        }
+
+        let mut x = vec![0.0; n];
+        for i in (0..n).rev() {
+            let known: f64 = (i + 1..n).map(|k| aug.get(i, k) * x[k]).sum();
+            x[i] = (aug.get(i, n) - known) / aug.get(i, i);
+        }
+        Ok(x)
+    }
+
+    fn swap_rows(&mut self, a: usize, b: usize) {
+        for k in 0..self.cols {
+            self.data.swap(a * self.cols + k, b * self.cols + k);
+        }
+    }
+}
+
+impl fmt::Display for Matrix {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let rows: Vec<String> = (0..self.rows)
+            .map(|i| {
+                let cells: Vec<String> = self.row(i).iter().map(|x| x.to_string()).collect();
+                format!("[{}]", cells.join(", "))
+            })
+            .collect();
+        write!(f, "[{}]", rows.join(", "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matrix(rows: &[&[f64]]) -> Matrix {
+        Matrix::from_rows(rows.iter().map(|r| r.to_vec()).collect()).unwrap()
+    }
+
+    #[test]
+    fn test_from_rows_checks_shape() {
+        assert!(Matrix::from_rows(vec![vec![1.0, 2.0], vec![3.0]]).is_err());
+        assert!(Matrix::from_rows(vec![]).is_err());
+        assert_eq!(
+            matrix(&[&[1.0, 2.0], &[3.0, 4.0]]).to_string(),
+            "[[1, 2], [3, 4]]"
+        );
+    }
+
+    #[test]
+    fn test_mul_and_transpose() {
+        let a = matrix(&[&[1.0, 2.0], &[3.0, 4.0]]);
+        let b = matrix(&[&[5.0], &[6.0]]);
+        assert_eq!(a.mul(&b).unwrap(), matrix(&[&[17.0], &[39.0]]));
+        assert!(b.mul(&a).is_err());
+        assert_eq!(b.transpose(), matrix(&[&[5.0, 6.0]]));
+        assert_eq!(
+            a.combine(&a, '-').unwrap(),
+            matrix(&[&[0.0, 0.0], &[0.0, 0.0]])
+        );
+        assert!(a.combine(&b, '+').is_err());
+    }
+
+    #[test]
+    fn test_det() {
+        assert_eq!(matrix(&[&[1.0, 2.0], &[3.0, 4.0]]).det().unwrap(), -2.0);
+        let singular = matrix(&[&[1.0, 2.0], &[2.0, 4.0]]);
+        assert_eq!(singular.det().unwrap(), 0.0);
+        assert!(matrix(&[&[1.0, 2.0]]).det().is_err());
+    }
+
+    #[test]
+    fn test_inverse() {
+        let a = matrix(&[&[4.0, 7.0], &[2.0, 6.0]]);
+        let inv = a.inverse().unwrap();
+        let identity = a.mul(&inv).unwrap();
+        for i in 0..2 {
+            for j in 0..2 {
+                let expected = if i == j { 1.0 } else { 0.0 };
+                assert!((identity.get(i, j) - expected).abs() < 1e-12);
+            }
+        }
+        assert!(matrix(&[&[1.0, 2.0], &[2.0, 4.0]]).inverse().is_err());
+    }
+
+    #[test]
+    fn test_solve() {
+        let a = matrix(&[&[2.0, 1.0, -1.0], &[-3.0, -1.0, 2.0], &[-2.0, 1.0, 2.0]]);
+        let x = a.solve(&[8.0, -11.0, -3.0]).unwrap();
+        for (got, want) in x.iter().zip([2.0, 3.0, -1.0]) {
+            assert!((got - want).abs() < 1e-12);
+        }
+
+        let singular = matrix(&[&[1.0, 2.0], &[2.0, 4.0]]);
+        let none = singular.solve(&[1.0, 3.0]).unwrap_err();
+        assert!(none.contains("no solution"));
+        let many = singular.solve(&[1.0, 2.0]).unwrap_err();
+        assert!(many.contains("infinitely many"));
+        assert!(singular.solve(&[1.0]).is_err());
+    }
+}
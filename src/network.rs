@@ -0,0 +1,111 @@
+//! IPv4 addresses and CIDR networks: `192.168.1.77`, `10.0.0.0/8`, or a bare
+//! prefix length like `/26`.
+
+/// Parses a dotted-quad address.
+pub fn parse_address(text: &str) -> Option<u32> {
+    let parts: Vec<&str> = text.split('.').collect();
+    if parts.len() != 4 {
+        return None;
+    }
+    parts.iter().try_fold(0u32, |acc, part| {
+        if part.is_empty() || part.len() > 3 || !part.chars().all(|c| c.is_ascii_digit()) {
+            return None;
+        }
+        let octet: u8 = part.parse().ok()?;
+        Some(acc << 8 | octet as u32)
+    })
+}
+
+/// Parses `address/prefix`, or `/prefix` alone (the address is then 0).
+pub fn parse_network(text: &str) -> Option<(u32, u8)> {
+    let (address, prefix) = text.split_once('/')?;
+    if prefix.is_empty() || prefix.len() > 2 || !prefix.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let prefix: u8 = prefix.parse().ok().filter(|&p| p <= 32)?;
+    let address = match address {
+        "" => 0,
+        _ => parse_address(address)?,
+    };
+    Some((address, prefix))
+}
+
+/// Length of the address or network literal at the start of `chars`, if
+/// there is one.
+pub fn scan_network(chars: &[char]) -> Option<usize> {
+    let len = chars
+        .iter()
+        .take_while(|c| c.is_ascii_digit() || **c == '.' || **c == '/')
+        .count();
+    let text: String = chars[..len].iter().collect();
+    (parse_address(&text).is_some() || parse_network(&text).is_some()).then_some(len)
+}
+
+pub fn format_address(address: u32) -> String {
+    let octets = address.to_be_bytes();
+    format!("{}.{}.{}.{}", octets[0], octets[1], octets[2], octets[3])
+}
+
+pub fn netmask(prefix: u8) -> u32 {
+    u32::MAX.checked_shl(32 - prefix as u32).unwrap_or(0)
+}
+
+/// Usable host addresses. A /31 is a point-to-point link with two hosts
+/// and a /32 a single host; otherwise the network and broadcast
+/// addresses are not counted.
+pub fn hosts(prefix: u8) -> u64 {
+    match prefix {
+        32 => 1,
+        31 => 2,
+        _ => (1u64 << (32 - prefix)) - 2,
+    }
+}
+
+pub fn contains(network: u32, prefix: u8, address: u32) -> bool {
+    let mask = netmask(prefix);
+    network & mask == address & mask
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_format() {
+        let address = parse_address("192.168.1.77").unwrap();
+        assert_eq!(format_address(address), "192.168.1.77");
+        assert_eq!(parse_address("192.168.1"), None);
+        assert_eq!(parse_address("256.1.1.1"), None);
+        assert_eq!(parse_network("10.0.0.0/8"), Some((0x0a00_0000, 8)));
+        assert_eq!(parse_network("/26"), Some((0, 26)));
+        assert_eq!(parse_network("/33"), None);
+    }
+
+    #[test]
+    fn test_scan_network() {
+        let chars: Vec<char> = "192.168.1.0/24)".chars().collect();
+        assert_eq!(scan_network(&chars), Some(14));
+        let chars: Vec<char> = "1.5 + 2".chars().collect();
+        assert_eq!(scan_network(&chars), None);
+    }
+
+    #[test]
+    fn test_masks_and_hosts() {
+        assert_eq!(format_address(netmask(20)), "255.255.240.0");
+        assert_eq!(netmask(0), 0);
+        assert_eq!(hosts(26), 62);
+        assert_eq!(hosts(31), 2);
+        assert_eq!(hosts(0), 4_294_967_294);
+        let network = parse_address("192.168.1.0").unwrap();
+        assert!(contains(
+            network,
+            24,
+            parse_address("192.168.1.77").unwrap()
+        ));
+        assert!(!contains(
+            network,
+            24,
+            parse_address("192.168.2.1").unwrap()
+        ));
+    }
+}
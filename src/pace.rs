@@ -0,0 +1,59 @@
+//! Running and driving arithmetic: pace, speed and fuel economy.
+
+const KM_PER_MILE: f64 = 1.609344;
+const LITRES_PER_GALLON: f64 = 3.785411784;
+
+fn check(distance: f64, seconds: f64) -> Result<(), String> {
+    if distance <= 0.0 || seconds <= 0.0 {
+        return Err(format!(
+            "Distance and time must be positive, got {} km and {} s",
+            distance, seconds
+        ));
+    }
+    Ok(())
+}
+
+/// Seconds per kilometre and per mile for `distance` km in `seconds`.
+pub fn pace(distance: f64, seconds: f64) -> Result<(f64, f64), String> {
+    check(distance, seconds)?;
+    let per_km = seconds / distance;
+    Ok((per_km, per_km * KM_PER_MILE))
+}
+
+/// Speed in km/h for `distance` km in `seconds`.
+pub fn speed(distance: f64, seconds: f64) -> Result<f64, String> {
+    check(distance, seconds)?;
+    Ok(distance / (seconds / 3600.0))
+}
+
+/// Converts between litres per 100 km and US miles per gallon. The
+/// conversion is its own inverse, so it serves both directions.
+pub fn fuel_economy(value: f64) -> Result<f64, String> {
+    if value <= 0.0 {
+        return Err(format!("Fuel economy must be positive, got {}", value));
+    }
+    Ok(100.0 * LITRES_PER_GALLON / KM_PER_MILE / value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pace_and_speed() {
+        let (per_km, per_mile) = pace(10.0, 3000.0).unwrap();
+        assert_eq!(per_km, 300.0);
+        assert!((per_mile - 482.8032).abs() < 1e-9);
+        assert_eq!(speed(42.195, 3.0 * 3600.0).unwrap(), 14.065);
+        assert!(pace(0.0, 60.0).is_err());
+        assert!(speed(5.0, -1.0).is_err());
+    }
+
+    #[test]
+    fn test_fuel_economy() {
+        let mpg = fuel_economy(5.0).unwrap();
+        assert!((mpg - 47.042_916_666).abs() < 1e-6);
+        assert!((fuel_economy(mpg).unwrap() - 5.0).abs() < 1e-12);
+        assert!(fuel_economy(0.0).is_err());
+    }
+}
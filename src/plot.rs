@@ -0,0 +1,172 @@
+//! Character-cell graphs for `plot(expr, x, a, b)`.
+
+use crate::calculus::sample;
+use crate::env::Env;
+use crate::expr::{Expr, eval};
+use crate::value::Value;
+
+const WIDTH: usize = 64;
+const HEIGHT: usize = 16;
+
+/// Points listed by `table`, the text form for `--accessible`.
+const TABLE_POINTS: usize = 11;
+
+/// Rounds an axis label to 4 significant digits.
+fn label(y: f64) -> String {
+    let rounded: f64 = format!("{:.3e}", y).parse().unwrap_or(y);
+    rounded.to_string()
+}
+
+fn bound(arg: &Expr, env: &mut Env) -> Result<f64, String> {
+    match eval(arg, env)? {
+        Value::Number(n) => Ok(n),
+        other => Err(format!(
+            "plot() expects numbers as bounds, got {}",
+            other.kind()
+        )),
+    }
+}
+
+/// The expression, variable and bounds of a `plot()` call.
+fn plot_args<'a>(args: &'a [Expr], env: &mut Env) -> Result<(&'a Expr, &'a str, f64, f64), String> {
+    let [expr, var, a, b] = args else {
+        return Err(format!(
+            "plot() takes an expression, a variable and two bounds, got {} argument(s)",
+            args.len()
+        ));
+    };
+    let Expr::Name(var) = var else {
+        return Err("plot() expects a variable name such as x".to_string());
+    };
+    let (a, b) = (bound(a, env)?, bound(b, env)?);
+    if a >= b {
+        return Err(format!("plot() needs a < b, got {} and {}", a, b));
+    }
+    Ok((expr, var, a, b))
+}
+
+/// The values of a `plot()` call at evenly spaced points, one line each,
+/// for screen readers in place of the graph.
+pub fn table(args: &[Expr], env: &mut Env) -> Result<String, String> {
+    let (expr, var, a, b) = plot_args(args, env)?;
+    let lines: Vec<String> = (0..TABLE_POINTS)
+        .map(|i| {
+            let x = a + (b - a) * i as f64 / (TABLE_POINTS - 1) as f64;
+            let y = match sample(expr, var, x, env) {
+                Ok(y) => label(y),
+                Err(_) => "undefined".to_string(),
+            };
+            format!("at {} = {}, {}", var, label(x), y)
+        })
+        .collect();
+    Ok(lines.join("\n"))
+}
+
+/// Samples `args[0]` across `[args[2], args[3]]` and draws it. Points where
+/// the expression is undefined are left out.
+pub fn plot(args: &[Expr], env: &mut Env) -> Result<String, String> {
+    let (expr, var, a, b) = plot_args(args, env)?;
+    let xs: Vec<f64> = (0..WIDTH)
+        .map(|i| a + (b - a) * i as f64 / (WIDTH - 1) as f64)
+        .collect();
+    let ys: Vec<Option<f64>> = xs.iter().map(|&x| sample(expr, var, x, env).ok()).collect();
+    let values = ys.iter().flatten();
+    let mut low = values.clone().copied().fold(f64::INFINITY, f64::min);
+    let mut high = values.copied().fold(f64::NEG_INFINITY, f64::max);
+    if low > high {
+        return Err(format!("plot() found no values between {} and {}", a, b));
+    }
+    if high - low < 1e-12 * high.abs().max(1.0) {
+        (low, high) = (low - 1.0, high + 1.0);
+    }
+    let row_of = |y: f64| ((high - y) / (high - low) * (HEIGHT - 1) as f64).round() as usize;
+
+    let mut grid = vec![vec![' '; WIDTH]; HEIGHT];
+    let zero_row = (low <= 0.0 && 0.0 <= high).then(|| row_of(0.0));
+    if let Some(row) = zero_row {
+        grid[row] = vec!['-'; WIDTH];
+    }
+    // The y axis, unless it would sit right on the left border.
+    if a < 0.0 && 0.0 <= b {
+        let col = (-a / (b - a) * (WIDTH - 1) as f64).round() as usize;
+        for (row, line) in grid.iter_mut().enumerate() {
+            line[col] = if Some(row) == zero_row { '+' } else { '|' };
+        }
+    }
+    for (col, y) in ys.iter().enumerate() {
+        if let Some(y) = y {
+            grid[row_of(*y)][col] = '*';
+        }
+    }
+
+    let labels: Vec<String> = (0..HEIGHT)
+        .map(|row| match row {
+            0 => label(high),
+            _ if row == HEIGHT - 1 => label(low),
+            _ if Some(row) == zero_row => "0".to_string(),
+            _ => String::new(),
+        })
+        .collect();
+    let margin = labels.iter().map(String::len).max().unwrap_or(0);
+    let mut lines: Vec<String> = grid
+        .iter()
+        .zip(&labels)
+        .map(|(row, text)| format!("{:>margin$} |{}", text, row.iter().collect::<String>()))
+        .collect();
+    let (left, right) = (label(a), label(b));
+    lines.push(format!(
+        "{:margin$}  {}{:>width$}",
+        "",
+        left,
+        right,
+        width = WIDTH - left.len()
+    ));
+    Ok(lines.join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn call(args: Vec<Expr>) -> Result<String, String> {
+        plot(&args, &mut Env::default())
+    }
+
+    fn num(n: f64) -> Expr {
+        Expr::Literal(Value::Number(n))
+    }
+
+    #[test]
+    fn test_line_spans_the_grid() {
+        let x = Expr::Name("x".into());
+        let text = call(vec![x.clone(), x, num(-1.0), num(1.0)]).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), HEIGHT + 1);
+        // y = x rises from the bottom left to the top right.
+        assert!(lines[0].starts_with(" 1 |") && lines[0].ends_with('*'));
+        assert!(lines[HEIGHT - 1].starts_with("-1 |*"));
+        assert!(lines[HEIGHT].trim_start().starts_with("-1"));
+    }
+
+    #[test]
+    fn test_table() {
+        let x = Expr::Name("x".into());
+        let square = Expr::Binary('*', Box::new(x.clone()), Box::new(x.clone()));
+        let text = table(&[square, x, num(0.0), num(1.0)], &mut Env::default()).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines.len(), TABLE_POINTS);
+        assert_eq!(lines[0], "at x = 0, 0");
+        assert_eq!(lines[5], "at x = 0.5, 0.25");
+        assert_eq!(lines[10], "at x = 1, 1");
+    }
+
+    #[test]
+    fn test_plot_errors() {
+        let x = Expr::Name("x".into());
+        assert!(call(vec![x.clone(), x.clone(), num(1.0), num(-1.0)]).is_err());
+        assert!(call(vec![x.clone(), num(2.0), num(0.0), num(1.0)]).is_err());
+        assert!(call(vec![x.clone(), x.clone(), num(0.0)]).is_err());
+        let undefined = Expr::Name("y".into());
+        assert!(call(vec![undefined, x, num(0.0), num(1.0)]).is_err());
+    }
+}
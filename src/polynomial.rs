@@ -0,0 +1,269 @@
+//! Real roots of polynomials given by their coefficients, highest power
+//! first: `[1, -3, 2]` is x² - 3x + 2.
+
+use std::f64::consts::PI;
+
+/// Value of the polynomial at `x` (Horner's scheme).
+pub fn eval(coeffs: &[f64], x: f64) -> f64 {
+    coeffs.iter().fold(0.0, |acc, c| acc * x + c)
+}
+
+fn derivative(coeffs: &[f64]) -> Vec<f64> {
+    let degree = coeffs.len() - 1;
+    coeffs[..degree]
+        .iter()
+        .enumerate()
+        .map(|(i, c)| c * (degree - i) as f64)
+        .collect()
+}
+
+/// A few Newton steps to clean up rounding in a closed-form root. Steps that
+/// do not bring the value closer to zero are dropped, so repeated roots
+/// (where the derivative vanishes) are left alone. Finally `1.0000000000000002`
+/// becomes `1` when the rounded value fits at least as well.
+fn polish(coeffs: &[f64], mut x: f64) -> f64 {
+    let slope = derivative(coeffs);
+    for _ in 0..3 {
+        let d = eval(&slope, x);
+        if d == 0.0 {
+            break;
+        }
+        let next = x - eval(coeffs, x) / d;
+        if eval(coeffs, next).abs() >= eval(coeffs, x).abs() {
+            break;
+        }
+        x = next;
+    }
+    let rounded = format!("{:.12e}", x).parse::<f64>().unwrap_or(x);
+    if eval(coeffs, rounded).abs() <= eval(coeffs, x).abs() {
+        rounded
+    } else {
+        x
+    }
+}
+
+fn quadratic(a: f64, b: f64, c: f64) -> Vec<f64> {
+    let disc = b * b - 4.0 * a * c;
+    if disc < -1e-12 * (b * b).max((4.0 * a * c).abs()) {
+        return vec![];
+    }
+    if disc <= 0.0 {
+        let root = -b / (2.0 * a);
+        return vec![root, root];
+    }
+    // Avoids cancellation between -b and the square root.
+    let q = -0.5 * (b + b.signum() * disc.sqrt());
+    if q == 0.0 {
+        return vec![0.0, 0.0];
+    }
+    vec![q / a, c / q]
+}
+
+fn cubic(a: f64, b: f64, c: f64, d: f64) -> Vec<f64> {
+    let (b, c, d) = (b / a, c / a, d / a);
+    // Substituting x = t - b/3 gives t³ + pt + q = 0.
+    let p = c - b * b / 3.0;
+    let q = 2.0 * b.powi(3) / 27.0 - b * c / 3.0 + d;
+    let shift = -b / 3.0;
+    let disc = (q / 2.0).powi(2) + (p / 3.0).powi(3);
+    let scale = (q / 2.0).powi(2).max((p / 3.0).abs().powi(3));
+
+    if disc.abs() <= 1e-12 * scale || (p == 0.0 && q == 0.0) {
+        if p == 0.0 {
+            return vec![shift; 3];
+        }
+        let double = -1.5 * q / p;
+        return vec![3.0 * q / p + shift, double + shift, double + shift];
+    }
+    if disc > 0.0 {
+        let root = disc.sqrt();
+        let t = (-q / 2.0 + root).cbrt() + (-q / 2.0 - root).cbrt();
+        return vec![t + shift];
+    }
+    let r = 2.0 * (-p / 3.0).sqrt();
+    let angle = ((3.0 * q / (2.0 * p)) * (-3.0 / p).sqrt())
+        .clamp(-1.0, 1.0)
+        .acos()
+        / 3.0;
+    (0..3)
+        .map(|k| r * (angle - 2.0 * PI * k as f64 / 3.0).cos() + shift)
+        .collect()
+}
+
+/// All roots of a monic polynomial by Durand-Kerner iteration, as
+/// `(re, im)` pairs.
+fn durand_kerner(coeffs: &[f64]) -> Vec<(f64, f64)> {
+    let degree = coeffs.len() - 1;
+    let mul = |(a, b): (f64, f64), (c, d): (f64, f64)| (a * c - b * d, a * d + b * c);
+    let div = |(a, b): (f64, f64), (c, d): (f64, f64)| {
+        let n = c * c + d * d;
+        ((a * c + b * d) / n, (b * c - a * d) / n)
+    };
+    let value_at = |z: (f64, f64)| {
+        coeffs.iter().fold((0.0, 0.0), |acc, &c| {
+            let (re, im) = mul(acc, z);
+            (re + c, im)
+        })
+    };
+
+    let mut roots: Vec<(f64, f64)> = Vec::with_capacity(degree);
+    let mut z = (1.0, 0.0);
+    for _ in 0..degree {
+        roots.push(z);
+        z = mul(z, (0.4, 0.9));
+    }
+    for _ in 0..1000 {
+        let mut moved = 0.0_f64;
+        for i in 0..degree {
+            let mut denominator = (1.0, 0.0);
+            for j in (0..degree).filter(|&j| j != i) {
+                denominator = mul(
+                    denominator,
+                    (roots[i].0 - roots[j].0, roots[i].1 - roots[j].1),
+                );
+            }
+            let step = div(value_at(roots[i]), denominator);
+            roots[i] = (roots[i].0 - step.0, roots[i].1 - step.1);
+            moved = moved.max(step.0.hypot(step.1));
+        }
+        if moved < 1e-14 {
+            break;
+        }
+    }
+    roots
+}
+
+/// Real roots in ascending order, repeated according to multiplicity.
+pub fn real_roots(coeffs: &[f64]) -> Result<Vec<f64>, String> {
+    let start = coeffs.iter().position(|&c| c != 0.0);
+    let coeffs = match start {
+        Some(start) if start + 1 < coeffs.len() => &coeffs[start..],
+        _ => return Err("roots() needs a polynomial of degree 1 or higher".to_string()),
+    };
+    // Trailing zero coefficients are roots at x = 0.
+    let zeros = coeffs.iter().rev().take_while(|&&c| c == 0.0).count();
+    let reduced = &coeffs[..coeffs.len() - zeros];
+
+    let mut roots = match reduced {
+        [_] => vec![],
+        [a, b] => vec![-b / a],
+        [a, b, c] => quadratic(*a, *b, *c),
+        [a, b, c, d] => cubic(*a, *b, *c, *d),
+        _ => {
+            let monic: Vec<f64> = reduced.iter().map(|c| c / reduced[0]).collect();
+            let scale = monic.iter().fold(1.0, |acc: f64, c| acc.max(c.abs()));
+            durand_kerner(&monic)
+                .into_iter()
+                .filter(|(re, im)| im.abs() <= 1e-7 * scale.max(re.abs()))
+                .map(|(re, _)| re)
+                .collect()
+        }
+    };
+    for root in roots.iter_mut() {
+        *root = polish(reduced, *root);
+    }
+    roots.extend(std::iter::repeat_n(0.0, zeros));
+    roots.sort_by(f64::total_cmp);
+    Ok(roots)
+}
+
+/// The discriminant of a quadratic or cubic: positive when all roots are
+/// real and distinct, zero for a repeated root.
+pub fn discriminant(coeffs: &[f64]) -> f64 {
+    match *coeffs {
+        [a, b, c] => b * b - 4.0 * a * c,
+        [a, b, c, d] => {
+            18.0 * a * b * c * d - 4.0 * b.powi(3) * d + b * b * c * c
+                - 4.0 * a * c.powi(3)
+                - 27.0 * a * a * d * d
+        }
+        _ => f64::NAN,
+    }
+}
+
+/// The complex pair `re ± im·i` of a quadratic or cubic with a negative
+/// discriminant, found by dividing the real root out of a cubic. `im` is
+/// positive.
+pub fn complex_pair(coeffs: &[f64]) -> Option<(f64, f64)> {
+    let (a, b, c) = match *coeffs {
+        [a, b, c] => (a, b, c),
+        [a, b, c, _] => {
+            let [root] = real_roots(coeffs).ok()?[..] else {
+                return None;
+            };
+            (a, b + a * root, c + (b + a * root) * root)
+        }
+        _ => return None,
+    };
+    let disc = b * b - 4.0 * a * c;
+    (disc < 0.0).then(|| (-b / (2.0 * a), (-disc).sqrt() / (2.0 * a).abs()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_roots(coeffs: &[f64], expected: &[f64]) {
+        let roots = real_roots(coeffs).unwrap();
+        assert_eq!(
+            roots.len(),
+            expected.len(),
+            "roots of {:?}: {:?}",
+            coeffs,
+            roots
+        );
+        for (got, want) in roots.iter().zip(expected) {
+            assert!(
+                (got - want).abs() < 1e-9,
+                "roots of {:?}: {:?}",
+                coeffs,
+                roots
+            );
+        }
+    }
+
+    #[test]
+    fn test_low_degree() {
+        assert_roots(&[2.0, -4.0], &[2.0]);
+        assert_roots(&[1.0, -3.0, 2.0], &[1.0, 2.0]);
+        assert_roots(&[1.0, -2.0, 1.0], &[1.0, 1.0]);
+        assert_roots(&[1.0, 0.0, 1.0], &[]);
+        assert_roots(&[0.0, 1.0, -3.0, 2.0], &[1.0, 2.0]);
+    }
+
+    #[test]
+    fn test_cubic() {
+        assert_roots(&[1.0, -6.0, 11.0, -6.0], &[1.0, 2.0, 3.0]);
+        assert_roots(&[1.0, 0.0, 0.0, -8.0], &[2.0]);
+        assert_roots(&[1.0, -3.0, 3.0, -1.0], &[1.0, 1.0, 1.0]);
+        assert_roots(&[1.0, 0.0, -1.0, 0.0], &[-1.0, 0.0, 1.0]);
+    }
+
+    #[test]
+    fn test_higher_degree() {
+        assert_roots(&[1.0, 0.0, -5.0, 0.0, 4.0], &[-2.0, -1.0, 1.0, 2.0]);
+        assert_roots(&[1.0, 0.0, 0.0, 0.0, 1.0], &[]);
+        assert_roots(
+            &[1.0, -15.0, 85.0, -225.0, 274.0, -120.0],
+            &[1.0, 2.0, 3.0, 4.0, 5.0],
+        );
+    }
+
+    #[test]
+    fn test_discriminant_and_complex_pair() {
+        assert_eq!(discriminant(&[1.0, 2.0, 5.0]), -16.0);
+        assert_eq!(complex_pair(&[1.0, 2.0, 5.0]), Some((-1.0, 2.0)));
+        assert_eq!(complex_pair(&[1.0, -3.0, 2.0]), None);
+        assert_eq!(discriminant(&[1.0, -6.0, 11.0, -6.0]), 4.0);
+        assert_eq!(discriminant(&[1.0, 0.0, 0.0, -8.0]), -1728.0);
+        let (re, im) = complex_pair(&[1.0, 0.0, 0.0, -8.0]).unwrap();
+        assert!((re + 1.0).abs() < 1e-12 && (im - 3f64.sqrt()).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_constant_rejected() {
+        assert!(real_roots(&[5.0]).is_err());
+        assert!(real_roots(&[0.0, 0.0]).is_err());
+        assert!(real_roots(&[]).is_err());
+    }
+}
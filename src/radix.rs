@@ -0,0 +1,129 @@
+const DIGITS: &[u8; 36] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+
+pub fn check_base(base: i64) -> Result<u32, String> {
+    if (2..=36).contains(&base) {
+        Ok(base as u32)
+    } else {
+        Err(format!("Bases go from 2 to 36, got {}", base))
+    }
+}
+
+/// Writes `n` in the given base (2..=36), with upper-case letters for
+/// digits past 9.
+pub fn to_base(n: i64, base: u32) -> String {
+    let mut magnitude = n.unsigned_abs();
+    let mut digits = vec![];
+    loop {
+        digits.push(DIGITS[(magnitude % base as u64) as usize]);
+        magnitude /= base as u64;
+        if magnitude == 0 {
+            break;
+        }
+    }
+    if n < 0 {
+        digits.push(b'-');
+    }
+    digits.iter().rev().map(|&d| d as char).collect()
+}
+
+/// Most digits written after the point by `fraction_to_base`.
+const MAX_FRACTION_DIGITS: usize = 64;
+
+/// Writes a non-integer in the given base, taking `x` to be the decimal it
+/// prints as (so 0.1 is exactly 1/10). A repeating tail is put in
+/// parentheses, `0.0(0011)`, and an expansion cut short ends in `...`.
+pub fn fraction_to_base(x: f64, base: u32) -> Result<String, String> {
+    let text = x.abs().to_string();
+    let (whole, decimals) = text.split_once('.').unwrap_or((&text, ""));
+    if decimals.len() > 30 {
+        return Err(format!("{} has too many decimals to convert", x));
+    }
+    let whole: i64 = whole
+        .parse()
+        .map_err(|_| format!("{} is too large to convert", x))?;
+    let denominator = 10u128.pow(decimals.len() as u32);
+    let mut remainder: u128 = decimals.parse().unwrap_or(0);
+    let mut seen = vec![];
+    let mut digits = String::new();
+    let mut ending = "";
+    while remainder != 0 {
+        if let Some(start) = seen.iter().position(|&r| r == remainder) {
+            digits.insert(start, '(');
+            ending = ")";
+            break;
+        }
+        if seen.len() == MAX_FRACTION_DIGITS {
+            ending = "...";
+            break;
+        }
+        seen.push(remainder);
+        remainder *= base as u128;
+        digits.push(DIGITS[(remainder / denominator) as usize] as char);
+        remainder %= denominator;
+    }
+    let sign = if x < 0.0 { "-" } else { "" };
+    let point = if digits.is_empty() { "" } else { "." };
+    Ok(format!(
+        "{}{}{}{}{}",
+        sign,
+        to_base(whole, base),
+        point,
+        digits,
+        ending
+    ))
+}
+
+/// Reads an integer written in the given base. Letters may be either case.
+pub fn from_base(text: &str, base: u32) -> Option<i64> {
+    let text = text.trim();
+    let (negative, digits) = match text.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, text),
+    };
+    if digits.is_empty() {
+        return None;
+    }
+    let magnitude = digits.chars().try_fold(0i64, |acc, c| {
+        let digit = c.to_digit(base)?;
+        acc.checked_mul(base as i64)?.checked_add(digit as i64)
+    })?;
+    Some(if negative { -magnitude } else { magnitude })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_base() {
+        assert_eq!(to_base(255, 16), "FF");
+        assert_eq!(to_base(5, 2), "101");
+        assert_eq!(to_base(35, 36), "Z");
+        assert_eq!(to_base(0, 8), "0");
+        assert_eq!(to_base(-10, 2), "-1010");
+    }
+
+    #[test]
+    fn test_fraction_to_base() {
+        assert_eq!(fraction_to_base(0.1, 2).unwrap(), "0.0(0011)");
+        assert_eq!(fraction_to_base(0.5, 2).unwrap(), "0.1");
+        assert_eq!(fraction_to_base(-2.75, 16).unwrap(), "-2.C");
+        assert_eq!(fraction_to_base(0.1, 3).unwrap(), "0.(0022)");
+        assert_eq!(
+            fraction_to_base(1.0 / 3.0, 10).unwrap(),
+            "0.3333333333333333"
+        );
+        let long = fraction_to_base(0.123456789, 2).unwrap();
+        assert!(long.ends_with("...") && long.len() == 2 + 64 + 3);
+    }
+
+    #[test]
+    fn test_from_base() {
+        assert_eq!(from_base("z", 36), Some(35));
+        assert_eq!(from_base("ff", 16), Some(255));
+        assert_eq!(from_base("-101", 2), Some(-5));
+        assert_eq!(from_base("12", 2), None);
+        assert_eq!(from_base("", 10), None);
+        assert!(check_base(1).is_err() && check_base(37).is_err());
+    }
+}
@@ -0,0 +1,72 @@
+const NUMERALS: [(u32, &str); 13] = [
+    (1000, "M"),
+    (900, "CM"),
+    (500, "D"),
+    (400, "CD"),
+    (100, "C"),
+    (90, "XC"),
+    (50, "L"),
+    (40, "XL"),
+    (10, "X"),
+    (9, "IX"),
+    (5, "V"),
+    (4, "IV"),
+    (1, "I"),
+];
+
+/// Writes `n` (1..=3999) in standard Roman numerals.
+pub fn to_roman(mut n: u32) -> Option<String> {
+    if !(1..=3999).contains(&n) {
+        return None;
+    }
+    let mut out = String::new();
+    for &(value, symbol) in NUMERALS.iter() {
+        while n >= value {
+            out.push_str(symbol);
+            n -= value;
+        }
+    }
+    Some(out)
+}
+
+/// Reads a Roman numeral, accepting only the canonical spelling
+/// (`IV`, not `IIII`). Case-insensitive.
+pub fn from_roman(text: &str) -> Option<u32> {
+    let upper = text.trim().to_uppercase();
+    let mut rest = upper.as_str();
+    let mut total = 0;
+    for &(value, symbol) in NUMERALS.iter() {
+        while let Some(tail) = rest.strip_prefix(symbol) {
+            total += value;
+            rest = tail;
+        }
+    }
+    if rest.is_empty() && to_roman(total).as_deref() == Some(upper.as_str()) {
+        Some(total)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_roman() {
+        assert_eq!(to_roman(2024).unwrap(), "MMXXIV");
+        assert_eq!(to_roman(1999).unwrap(), "MCMXCIX");
+        assert_eq!(to_roman(3999).unwrap(), "MMMCMXCIX");
+        assert_eq!(to_roman(0), None);
+        assert_eq!(to_roman(4000), None);
+    }
+
+    #[test]
+    fn test_from_roman() {
+        assert_eq!(from_roman("MCMXCIX"), Some(1999));
+        assert_eq!(from_roman("mmxxiv"), Some(2024));
+        assert_eq!(from_roman("IIII"), None);
+        assert_eq!(from_roman("ABC"), None);
+        assert_eq!(from_roman(""), None);
+    }
+}
@@ -0,0 +1,119 @@
+//! Reverse Polish input for `mode rpn`: `3 4 + 5 *` pushes 3 and 4, adds
+//! them, pushes 5 and multiplies. The stack is cleared with `clst` (as on
+//! the HP-42S), since `clear` already clears the history.
+
+use crate::value::{Value, apply_values};
+
+#[derive(Debug, Default)]
+pub struct Stack {
+    items: Vec<Value>,
+}
+
+impl Stack {
+    /// Runs every word of `line` in turn. If one fails the stack is left as
+    /// it was before the line.
+    pub fn run(&mut self, line: &str) -> Result<(), String> {
+        let mut items = self.items.clone();
+        for word in line.split_whitespace() {
+            apply_word(&mut items, word)?;
+        }
+        self.items = items;
+        Ok(())
+    }
+
+    pub fn top(&self) -> Option<&Value> {
+        self.items.last()
+    }
+
+    /// One line per level, numbered like an HP calculator with level 1
+    /// (the top) at the bottom.
+    pub fn describe(&self) -> String {
+        if self.items.is_empty() {
+            return "(empty stack)".to_string();
+        }
+        let count = self.items.len();
+        self.items
+            .iter()
+            .enumerate()
+            .map(|(i, value)| format!("{}: {}", count - i, value))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+fn pop(items: &mut Vec<Value>, word: &str, needed: usize) -> Result<Vec<Value>, String> {
+    if items.len() < needed {
+        return Err(format!(
+            "{} needs {} value(s) on the stack, found {}",
+            word,
+            needed,
+            items.len()
+        ));
+    }
+    Ok(items.split_off(items.len() - needed))
+}
+
+fn apply_word(items: &mut Vec<Value>, word: &str) -> Result<(), String> {
+    match word {
+        "+" | "-" | "*" | "/" | "^" | "%" => {
+            let mut operands = pop(items, word, 2)?.into_iter();
+            let (a, b) = (operands.next().unwrap(), operands.next().unwrap());
+            items.push(apply_values(a, word.chars().next().unwrap(), b)?);
+        }
+        "drop" => {
+            pop(items, word, 1)?;
+        }
+        "dup" => {
+            let top = pop(items, word, 1)?;
+            items.extend(top.clone());
+            items.extend(top);
+        }
+        "swap" => {
+            let pair = pop(items, word, 2)?;
+            items.extend(pair.into_iter().rev());
+        }
+        "clst" => items.clear(),
+        _ => match word.parse::<f64>() {
+            Ok(n) => items.push(Value::Number(n)),
+            Err(_) => return Err(format!("Unknown RPN word: {}", word)),
+        },
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(line: &str) -> Result<Stack, String> {
+        let mut stack = Stack::default();
+        stack.run(line)?;
+        Ok(stack)
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let stack = run("3 4 + 5 *").unwrap();
+        assert_eq!(stack.top(), Some(&Value::Number(35.0)));
+        assert_eq!(run("10 4 -").unwrap().top(), Some(&Value::Number(6.0)));
+        assert_eq!(run("2 -3 ^").unwrap().top(), Some(&Value::Number(0.125)));
+    }
+
+    #[test]
+    fn test_stack_words() {
+        assert_eq!(run("1 2 swap").unwrap().describe(), "2: 2\n1: 1");
+        assert_eq!(run("7 dup *").unwrap().describe(), "1: 49");
+        assert_eq!(run("1 2 drop").unwrap().describe(), "1: 1");
+        assert_eq!(run("1 2 clst").unwrap().describe(), "(empty stack)");
+    }
+
+    #[test]
+    fn test_errors_leave_the_stack_alone() {
+        let mut stack = run("1 2").unwrap();
+        assert!(stack.run("+ +").is_err());
+        assert_eq!(stack.describe(), "2: 1\n1: 2");
+        assert!(stack.run("1 0 /").is_err());
+        assert!(stack.run("frobnicate").is_err());
+        assert_eq!(stack.describe(), "2: 1\n1: 2");
+    }
+}
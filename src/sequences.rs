@@ -0,0 +1,82 @@
+use crate::bigint::BigUint;
+
+/// Largest index accepted, to keep the digits printable.
+pub const MAX_INDEX: u32 = 10_000;
+
+/// Largest index for `triangular`, the last whose result fits in a u64.
+/// It takes no loop, so the much smaller `MAX_INDEX` does not apply.
+pub const MAX_TRIANGULAR_INDEX: u64 = 6_074_000_999;
+
+fn check(name: &str, n: u32) -> Result<(), String> {
+    if n > MAX_INDEX {
+        Err(format!("{}() is limited to n <= {}", name, MAX_INDEX))
+    } else {
+        Ok(())
+    }
+}
+
+/// The n-th Fibonacci number, with fib(0) = 0 and fib(1) = 1.
+pub fn fib(n: u32) -> Result<BigUint, String> {
+    check("fib", n)?;
+    let (mut a, mut b) = (BigUint::from_u64(0), BigUint::from_u64(1));
+    for _ in 0..n {
+        (a, b) = (b.clone(), a.add(&b));
+    }
+    Ok(a)
+}
+
+/// 1 + 2 + ... + n.
+pub fn triangular(n: u64) -> Result<BigUint, String> {
+    if n > MAX_TRIANGULAR_INDEX {
+        return Err(format!(
+            "triangular() is limited to n <= {}",
+            MAX_TRIANGULAR_INDEX
+        ));
+    }
+    let n = n as u128;
+    Ok(BigUint::from_u64((n * (n + 1) / 2) as u64))
+}
+
+/// The n-th Catalan number, via C(k) = C(k-1) * 2(2k - 1) / (k + 1),
+/// where each division is exact.
+pub fn catalan(n: u32) -> Result<BigUint, String> {
+    check("catalan", n)?;
+    let mut c = BigUint::from_u64(1);
+    for k in 1..=n {
+        c = c.mul_small(2 * (2 * k - 1)).div_small(k + 1).0;
+    }
+    Ok(c)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_small_values() {
+        let fibs: Vec<String> = (0..10).map(|n| fib(n).unwrap().to_string()).collect();
+        assert_eq!(fibs, ["0", "1", "1", "2", "3", "5", "8", "13", "21", "34"]);
+        assert_eq!(triangular(4).unwrap().to_string(), "10");
+        let catalans: Vec<String> = (0..7).map(|n| catalan(n).unwrap().to_string()).collect();
+        assert_eq!(catalans, ["1", "1", "2", "5", "14", "42", "132"]);
+    }
+
+    #[test]
+    fn test_big_values() {
+        assert_eq!(fib(100).unwrap().to_string(), "354224848179261915075");
+        assert_eq!(
+            catalan(50).unwrap().to_string(),
+            "1978261657756160653623774456"
+        );
+        assert!(fib(MAX_INDEX + 1).is_err());
+        assert_eq!(
+            triangular(MAX_INDEX as u64 + 1).unwrap().to_string(),
+            "50015001"
+        );
+        assert_eq!(
+            triangular(MAX_TRIANGULAR_INDEX).unwrap().to_string(),
+            "18446744070963499500"
+        );
+        assert!(triangular(MAX_TRIANGULAR_INDEX + 1).is_err());
+    }
+}
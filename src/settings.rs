@@ -0,0 +1,281 @@
+use crate::vat;
+
+/// User-adjustable options, changed with `set <name> <value>` in the REPL
+/// or in `.calcrc`.
+#[derive(Debug, Clone, Default)]
+pub struct Settings {
+    /// Default VAT rate for `vat_add` / `vat_remove`, as a fraction.
+    pub vat_rate: Option<f64>,
+    /// Word size in bits for the bit functions; 64 unless set.
+    pub word_size: Option<u32>,
+    /// Results are padded with zeros to at least this many decimals.
+    pub min_decimals: Option<usize>,
+    /// Results are rounded to at most this many decimals.
+    pub max_decimals: Option<usize>,
+    /// File that failed expressions are appended to.
+    pub error_log: Option<String>,
+    /// Trigonometric functions work in degrees rather than radians.
+    pub degrees: bool,
+}
+
+/// Settings that take text, such as a file name, rather than a number.
+pub const TEXT_SETTINGS: [&str; 2] = ["errorlog", "angles"];
+
+fn decimals(name: &str, value: f64) -> Result<usize, String> {
+    if value.fract() == 0.0 && (0.0..=20.0).contains(&value) {
+        Ok(value as usize)
+    } else {
+        Err(format!(
+            "{} must be a whole number from 0 to 20, got {}",
+            name, value
+        ))
+    }
+}
+
+/// Padding past `maxdecimals` would show digits that were never worked out.
+fn check_decimals(min: usize, max: Option<usize>) -> Result<(), String> {
+    match max {
+        Some(max) if min > max => Err(format!(
+            "mindecimals ({}) cannot be more than maxdecimals ({})",
+            min, max
+        )),
+        _ => Ok(()),
+    }
+}
+
+impl Settings {
+    pub fn set(&mut self, name: &str, value: f64) -> Result<(), String> {
+        match name {
+            "vat" => self.vat_rate = Some(vat::check_rate(value)?),
+            "wordsize" => {
+                if ![8.0, 16.0, 32.0, 64.0].contains(&value) {
+                    return Err(format!("wordsize must be 8, 16, 32 or 64, got {}", value));
+                }
+                self.word_size = Some(value as u32);
+            }
+            "mindecimals" => {
+                let min = decimals(name, value)?;
+                check_decimals(min, self.max_decimals)?;
+                self.min_decimals = Some(min);
+            }
+            "maxdecimals" => {
+                let max = decimals(name, value)?;
+                check_decimals(self.min_decimals.unwrap_or(0), Some(max))?;
+                self.max_decimals = Some(max);
+            }
+            _ => return Err(format!("Unknown setting: {}", name)),
+        }
+        Ok(())
+    }
+
+    pub fn set_text(&mut self, name: &str, value: &str) -> Result<(), String> {
+        match name {
+            "errorlog" if value.is_empty() => return Err("errorlog needs a file name".to_string()),
+            "errorlog" => self.error_log = Some(value.to_string()),
+            "angles" => {
+                self.degrees = match value {
+                    "degrees" | "deg" => true,
+                    "radians" | "rad" => false,
+                    _ => return Err(format!("angles must be degrees or radians, got {}", value)),
+                }
+            }
+            _ => return Err(format!("Unknown setting: {}", name)),
+        }
+        Ok(())
+    }
+
+    /// Puts a setting back to its default (`set <name> off`).
+    pub fn reset(&mut self, name: &str) -> Result<(), String> {
+        match name {
+            "vat" => self.vat_rate = None,
+            "wordsize" => self.word_size = None,
+            "mindecimals" => self.min_decimals = None,
+            "maxdecimals" => self.max_decimals = None,
+            "errorlog" => self.error_log = None,
+            "angles" => self.degrees = false,
+            _ => return Err(format!("Unknown setting: {}", name)),
+        }
+        Ok(())
+    }
+
+    /// Shows a result number with the decimals settings applied. With
+    /// neither set this is the usual shortest form, so `8` stays `8`.
+    pub fn format_number(&self, n: f64) -> String {
+        if !n.is_finite() {
+            return n.to_string();
+        }
+        let mut text = match self.max_decimals {
+            Some(max) => {
+                let rounded = format!("{:.*}", max, n);
+                let rounded = match rounded.contains('.') {
+                    true => rounded.trim_end_matches('0').trim_end_matches('.'),
+                    false => &rounded,
+                };
+                // Rounding a small negative number can leave "-0".
+                if rounded == "-0" { "0" } else { rounded }.to_string()
+            }
+            None => n.to_string(),
+        };
+        let min = self.min_decimals.unwrap_or(0);
+        let shown = text
+            .split_once('.')
+            .map_or(0, |(_, fraction)| fraction.len());
+        if shown < min {
+            if shown == 0 {
+                text.push('.');
+            }
+            text.push_str(&"0".repeat(min - shown));
+        }
+        text
+    }
+
+    /// One `name = value` line per setting.
+    pub fn describe(&self) -> Vec<String> {
+        let vat = match self.vat_rate {
+            Some(rate) => vat::format_rate(rate),
+            None => "not set".to_string(),
+        };
+        let max_decimals = match self.max_decimals {
+            Some(max) => max.to_string(),
+            None => "no limit".to_string(),
+        };
+        vec![
+            format!("vat = {}", vat),
+            format!("wordsize = {}", self.word_size()),
+            format!("mindecimals = {}", self.min_decimals.unwrap_or(0)),
+            format!("maxdecimals = {}", max_decimals),
+            format!("errorlog = {}", self.error_log.as_deref().unwrap_or("off")),
+            format!(
+                "angles = {}",
+                if self.degrees { "degrees" } else { "radians" }
+            ),
+        ]
+    }
+
+    /// Degrees in the current angle unit.
+    pub fn degrees_to_angle(&self, degrees: f64) -> f64 {
+        if self.degrees {
+            degrees
+        } else {
+            degrees.to_radians()
+        }
+    }
+
+    /// An angle in the current unit, in degrees.
+    pub fn angle_to_degrees(&self, angle: f64) -> f64 {
+        if self.degrees {
+            angle
+        } else {
+            angle.to_degrees()
+        }
+    }
+
+    /// An angle in the current unit, in radians.
+    pub fn angle_to_radians(&self, angle: f64) -> f64 {
+        if self.degrees {
+            angle.to_radians()
+        } else {
+            angle
+        }
+    }
+
+    /// Radians in the current angle unit.
+    pub fn radians_to_angle(&self, radians: f64) -> f64 {
+        if self.degrees {
+            radians.to_degrees()
+        } else {
+            radians
+        }
+    }
+
+    pub fn word_size(&self) -> u32 {
+        self.word_size.unwrap_or(64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set_vat() {
+        let mut settings = Settings::default();
+        assert_eq!(
+            settings.describe(),
+            vec![
+                "vat = not set",
+                "wordsize = 64",
+                "mindecimals = 0",
+                "maxdecimals = no limit",
+                "errorlog = off",
+                "angles = radians"
+            ]
+        );
+        settings.set("vat", 0.19).unwrap();
+        assert_eq!(settings.vat_rate, Some(0.19));
+        assert_eq!(settings.describe()[0], "vat = 19%");
+        // A bare 19 would be 1900%.
+        assert!(settings.set("vat", 19.0).is_err());
+        assert!(settings.set("vat", -0.01).is_err());
+        assert!(settings.set("colour", 1.0).is_err());
+    }
+
+    #[test]
+    fn test_format_number() {
+        let mut settings = Settings::default();
+        assert_eq!(settings.format_number(8.0), "8");
+        assert_eq!(settings.format_number(0.1 + 0.2), "0.30000000000000004");
+        settings.set("mindecimals", 2.0).unwrap();
+        assert_eq!(settings.format_number(8.0), "8.00");
+        assert_eq!(settings.format_number(8.5), "8.50");
+        assert_eq!(settings.format_number(0.125), "0.125");
+        settings.set("maxdecimals", 2.0).unwrap();
+        assert_eq!(settings.format_number(2.0 / 3.0), "0.67");
+        assert_eq!(settings.format_number(-0.001), "0.00");
+        settings.reset("mindecimals").unwrap();
+        assert_eq!(settings.format_number(1.999), "2");
+        assert_eq!(settings.format_number(f64::INFINITY), "inf");
+        assert!(settings.set("maxdecimals", 1.5).is_err());
+    }
+
+    #[test]
+    fn test_conflicting_decimals() {
+        let mut settings = Settings::default();
+        settings.set("mindecimals", 2.0).unwrap();
+        assert!(settings.set("maxdecimals", 1.0).is_err());
+        assert_eq!(settings.max_decimals, None);
+        settings.set("maxdecimals", 2.0).unwrap();
+        assert!(settings.set("mindecimals", 3.0).is_err());
+        assert_eq!(settings.format_number(2.0 / 3.0), "0.67");
+        assert!(settings.reset("colour").is_err());
+    }
+
+    #[test]
+    fn test_set_error_log() {
+        let mut settings = Settings::default();
+        settings.set_text("errorlog", "errors.log").unwrap();
+        assert_eq!(settings.error_log.as_deref(), Some("errors.log"));
+        assert!(settings.set_text("errorlog", "").is_err());
+        settings.reset("errorlog").unwrap();
+        assert_eq!(settings.error_log, None);
+    }
+
+    #[test]
+    fn test_set_angles() {
+        let mut settings = Settings::default();
+        assert_eq!(settings.degrees_to_angle(180.0), std::f64::consts::PI);
+        settings.set_text("angles", "degrees").unwrap();
+        assert_eq!(settings.degrees_to_angle(180.0), 180.0);
+        assert!(settings.set_text("angles", "gradians").is_err());
+        settings.reset("angles").unwrap();
+        assert!(!settings.degrees);
+    }
+
+    #[test]
+    fn test_set_word_size() {
+        let mut settings = Settings::default();
+        settings.set("wordsize", 16.0).unwrap();
+        assert_eq!(settings.word_size(), 16);
+        assert!(settings.set("wordsize", 12.0).is_err());
+    }
+}
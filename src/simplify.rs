@@ -0,0 +1,329 @@
+//! Algebraic simplification for the `simplify` command. An expression is
+//! read as a sum of terms, each a coefficient times powers of factors, so
+//! constants fold and like terms collect: `2*x + 3*x - x` becomes `4*x`.
+//! Whatever the rules do not cover (function calls, division by a sum, ...)
+//! is kept as a factor of its own.
+
+use crate::calculate;
+use crate::calculus::tidy;
+use crate::expr::{Expr, percent_change};
+use crate::value::Value;
+use std::collections::{BTreeMap, HashMap};
+
+/// Largest power of a sum that is multiplied out.
+const MAX_EXPANSION: i32 = 6;
+
+/// Factors by their printed form, with their powers.
+type Term = BTreeMap<String, i32>;
+
+/// Coefficients by term; the empty term holds the constant.
+#[derive(Debug, Clone, Default)]
+struct Sum(BTreeMap<Term, f64>);
+
+impl Sum {
+    fn constant(n: f64) -> Sum {
+        let mut sum = Sum::default();
+        sum.add_term(Term::new(), n);
+        sum
+    }
+
+    fn as_constant(&self) -> Option<f64> {
+        match self.0.iter().next() {
+            None => Some(0.0),
+            Some((term, c)) if self.0.len() == 1 && term.is_empty() => Some(*c),
+            _ => None,
+        }
+    }
+
+    fn single_term(&self) -> Option<(&Term, f64)> {
+        match self.0.iter().next() {
+            Some((term, c)) if self.0.len() == 1 => Some((term, *c)),
+            _ => None,
+        }
+    }
+
+    /// Adds `c * term`, dropping the term when it cancels out.
+    fn add_term(&mut self, term: Term, c: f64) {
+        let old = self.0.get(&term).copied().unwrap_or(0.0);
+        let total = old + c;
+        if total.abs() <= 1e-12 * old.abs().max(c.abs()) {
+            self.0.remove(&term);
+        } else {
+            self.0.insert(term, total);
+        }
+    }
+
+    fn add(mut self, other: Sum, sign: f64) -> Sum {
+        for (term, c) in other.0 {
+            self.add_term(term, sign * c);
+        }
+        self
+    }
+
+    fn scale(self, k: f64) -> Sum {
+        Sum::default().add(self, k)
+    }
+
+    fn mul(&self, other: &Sum) -> Sum {
+        let mut product = Sum::default();
+        for (a, ca) in &self.0 {
+            for (b, cb) in &other.0 {
+                let mut term = a.clone();
+                for (factor, power) in b {
+                    *term.entry(factor.clone()).or_insert(0) += power;
+                }
+                term.retain(|_, power| *power != 0);
+                product.add_term(term, ca * cb);
+            }
+        }
+        product
+    }
+
+    fn pow(&self, n: i32) -> Option<Sum> {
+        if let Some((term, c)) = self.single_term() {
+            if n < 0 && c == 0.0 {
+                return None;
+            }
+            let term = term.iter().map(|(f, p)| (f.clone(), p * n)).collect();
+            let mut power = Sum::default();
+            power.add_term(term, c.powi(n));
+            return Some(power);
+        }
+        if !(0..=MAX_EXPANSION).contains(&n) {
+            return None;
+        }
+        Some((0..n).fold(Sum::constant(1.0), |acc, _| acc.mul(self)))
+    }
+}
+
+#[derive(Default)]
+struct Simplifier {
+    /// The expression behind each factor name.
+    factors: HashMap<String, Expr>,
+}
+
+impl Simplifier {
+    fn factor(&mut self, expr: Expr) -> Sum {
+        let name = expr.to_string();
+        self.factors.insert(name.clone(), expr);
+        let mut sum = Sum::default();
+        sum.add_term(Term::from([(name, 1)]), 1.0);
+        sum
+    }
+
+    /// Folds `a op b` when both sides are constants and the result is a
+    /// plain number, otherwise keeps it as a factor.
+    fn fold_or_keep(&mut self, op: char, a: Sum, b: Sum) -> Sum {
+        if let (Some(x), Some(y)) = (a.as_constant(), b.as_constant())
+            && let Ok(n) = calculate(x, op, y)
+            && n.is_finite()
+        {
+            return Sum::constant(n);
+        }
+        let expr = Expr::Binary(op, Box::new(self.build(a)), Box::new(self.build(b)));
+        self.factor(expr)
+    }
+
+    fn collect(&mut self, expr: &Expr) -> Sum {
+        if let Some((op, base, rate)) = percent_change(expr) {
+            let sign = if op == '+' { 0.01 } else { -0.01 };
+            let factor = self.collect(rate).scale(sign).add(Sum::constant(1.0), 1.0);
+            return self.collect(base).mul(&factor);
+        }
+        match expr {
+            Expr::Literal(Value::Number(n)) => Sum::constant(*n),
+            Expr::Unary('-', operand) => self.collect(operand).scale(-1.0),
+            Expr::Unary('%', operand) => self.collect(operand).scale(0.01),
+            Expr::Unary(op, operand) => {
+                let inner = self.collect(operand);
+                match inner.as_constant().map(|x| calculate(x, *op, 0.0)) {
+                    Some(Ok(n)) if n.is_finite() => Sum::constant(n),
+                    _ => {
+                        let expr = Expr::Unary(*op, Box::new(self.build(inner)));
+                        self.factor(expr)
+                    }
+                }
+            }
+            Expr::Binary('+', a, b) => self.collect(a).add(self.collect(b), 1.0),
+            Expr::Binary('-', a, b) => self.collect(a).add(self.collect(b), -1.0),
+            Expr::Binary('*', a, b) => self.collect(a).mul(&self.collect(b)),
+            Expr::Binary('/', a, b) => {
+                let (a, b) = (self.collect(a), self.collect(b));
+                match b.pow(-1) {
+                    Some(inverse) => a.mul(&inverse),
+                    None => self.fold_or_keep('/', a, b),
+                }
+            }
+            Expr::Binary('^', a, b) => {
+                let (a, b) = (self.collect(a), self.collect(b));
+                let power = b
+                    .as_constant()
+                    .filter(|n| n.fract() == 0.0 && n.abs() <= 1000.0)
+                    .and_then(|n| a.pow(n as i32));
+                match power {
+                    Some(power) => power,
+                    None => self.fold_or_keep('^', a, b),
+                }
+            }
+            Expr::Binary(op, a, b) => {
+                let (a, b) = (self.collect(a), self.collect(b));
+                self.fold_or_keep(*op, a, b)
+            }
+            Expr::Call(name, args) => {
+                let args = args.iter().map(simplify).collect();
+                self.factor(Expr::Call(name.clone(), args))
+            }
+            other => self.factor(other.clone()),
+        }
+    }
+
+    fn product(&self, factors: &[(&String, i32)]) -> Option<Expr> {
+        factors
+            .iter()
+            .map(|(name, power)| {
+                let base = self.factors[*name].clone();
+                match power {
+                    1 => base,
+                    _ => Expr::Binary(
+                        '^',
+                        Box::new(base),
+                        Box::new(Expr::Literal(Value::Number(*power as f64))),
+                    ),
+                }
+            })
+            .reduce(times)
+    }
+
+    /// `c * term` for a positive `c`, with negative powers moved into a
+    /// denominator.
+    fn build_term(&self, term: &Term, c: f64) -> Expr {
+        let above: Vec<_> = term
+            .iter()
+            .filter(|(_, p)| **p > 0)
+            .map(|(f, p)| (f, *p))
+            .collect();
+        let below: Vec<_> = term
+            .iter()
+            .filter(|(_, p)| **p < 0)
+            .map(|(f, p)| (f, -p))
+            .collect();
+        let number = Expr::Literal(Value::Number(c));
+        let top = match self.product(&above) {
+            Some(top) if c == 1.0 => top,
+            Some(top) => times(number, top),
+            None => number,
+        };
+        match self.product(&below) {
+            Some(bottom) => Expr::Binary('/', Box::new(top), Box::new(bottom)),
+            None => top,
+        }
+    }
+
+    fn build(&self, sum: Sum) -> Expr {
+        let mut terms: Vec<(Term, f64)> = sum.0.into_iter().map(|(t, c)| (t, tidy(c))).collect();
+        // Highest degree first, the constant last.
+        terms.sort_by_key(|(term, _)| {
+            let degree: i32 = term.values().filter(|p| **p > 0).sum();
+            (term.is_empty(), std::cmp::Reverse(degree))
+        });
+
+        let mut result: Option<Expr> = None;
+        for (term, c) in terms {
+            let magnitude = self.build_term(&term, c.abs());
+            result = Some(match result {
+                None if c < 0.0 => negate(magnitude),
+                None => magnitude,
+                Some(acc) => {
+                    let op = if c < 0.0 { '-' } else { '+' };
+                    Expr::Binary(op, Box::new(acc), Box::new(magnitude))
+                }
+            });
+        }
+        result.unwrap_or(Expr::Literal(Value::Number(0.0)))
+    }
+}
+
+/// `a * b`, kept as one flat product when `b` is a product itself, so the
+/// result prints as `2*x*y` rather than `2*(x*y)`.
+fn times(a: Expr, b: Expr) -> Expr {
+    match b {
+        Expr::Binary('*', left, right) => times(times(a, *left), *right),
+        b => Expr::Binary('*', Box::new(a), Box::new(b)),
+    }
+}
+
+/// Negates a term, folding the sign into its coefficient where there is one.
+fn negate(expr: Expr) -> Expr {
+    match expr {
+        Expr::Literal(Value::Number(n)) => Expr::Literal(Value::Number(-n)),
+        Expr::Binary(op @ ('*' | '/'), a, b) if matches!(*a, Expr::Literal(_)) => {
+            Expr::Binary(op, Box::new(negate(*a)), b)
+        }
+        Expr::Binary('/', a, b) => Expr::Binary('/', Box::new(negate(*a)), b),
+        other => Expr::Unary('-', Box::new(other)),
+    }
+}
+
+pub fn simplify(expr: &Expr) -> Expr {
+    let mut simplifier = Simplifier::default();
+    let sum = simplifier.collect(expr);
+    simplifier.build(sum)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn num(n: f64) -> Box<Expr> {
+        Box::new(Expr::Literal(Value::Number(n)))
+    }
+
+    fn x() -> Box<Expr> {
+        Box::new(Expr::Name("x".into()))
+    }
+
+    #[test]
+    fn test_like_terms() {
+        // 2*x + 3*x - x
+        let expr = Expr::Binary(
+            '-',
+            Box::new(Expr::Binary(
+                '+',
+                Box::new(Expr::Binary('*', num(2.0), x())),
+                Box::new(Expr::Binary('*', num(3.0), x())),
+            )),
+            x(),
+        );
+        assert_eq!(simplify(&expr).to_string(), "4*x");
+
+        let cancelled = Expr::Binary('-', x(), x());
+        assert_eq!(simplify(&cancelled).to_string(), "0");
+    }
+
+    #[test]
+    fn test_constants_and_powers() {
+        let folded = Expr::Binary(
+            '*',
+            num(2.0),
+            Box::new(Expr::Binary('^', num(3.0), num(2.0))),
+        );
+        assert_eq!(simplify(&folded).to_string(), "18");
+
+        // x * x / x
+        let expr = Expr::Binary('/', Box::new(Expr::Binary('*', x(), x())), x());
+        assert_eq!(simplify(&expr).to_string(), "x");
+
+        let inverse = Expr::Binary('/', num(-3.0), x());
+        assert_eq!(simplify(&inverse).to_string(), "-3/x");
+    }
+
+    #[test]
+    fn test_unknown_parts_are_kept() {
+        let call = Expr::Call("f".into(), vec![Expr::Binary('+', num(1.0), num(1.0))]);
+        let doubled = Expr::Binary('+', Box::new(call.clone()), Box::new(call));
+        assert_eq!(simplify(&doubled).to_string(), "2*f(2)");
+
+        let by_zero = Expr::Binary('/', x(), num(0.0));
+        assert_eq!(simplify(&by_zero).to_string(), "x/0");
+    }
+}
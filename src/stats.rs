@@ -0,0 +1,173 @@
+fn require(values: &[f64], min: usize, name: &str) -> Result<(), String> {
+    if values.len() < min {
+        Err(format!("{}() needs at least {} value(s)", name, min))
+    } else {
+        Ok(())
+    }
+}
+
+pub fn mean(values: &[f64]) -> Result<f64, String> {
+    require(values, 1, "mean")?;
+    Ok(values.iter().sum::<f64>() / values.len() as f64)
+}
+
+pub fn median(values: &[f64]) -> Result<f64, String> {
+    require(values, 1, "median")?;
+    let mut sorted = values.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    let mid = sorted.len() / 2;
+    if sorted.len() % 2 == 1 {
+        Ok(sorted[mid])
+    } else {
+        Ok((sorted[mid - 1] + sorted[mid]) / 2.0)
+    }
+}
+
+fn squared_deviations(values: &[f64]) -> f64 {
+    let avg = values.iter().sum::<f64>() / values.len() as f64;
+    values.iter().map(|x| (x - avg).powi(2)).sum()
+}
+
+/// Sample variance (divides by n - 1).
+pub fn variance(values: &[f64]) -> Result<f64, String> {
+    require(values, 2, "variance")?;
+    Ok(squared_deviations(values) / (values.len() - 1) as f64)
+}
+
+/// Population variance (divides by n).
+pub fn pvariance(values: &[f64]) -> Result<f64, String> {
+    require(values, 1, "pvariance")?;
+    Ok(squared_deviations(values) / values.len() as f64)
+}
+
+pub fn stddev(values: &[f64]) -> Result<f64, String> {
+    require(values, 2, "stddev")?;
+    Ok(variance(values)?.sqrt())
+}
+
+pub fn pstddev(values: &[f64]) -> Result<f64, String> {
+    require(values, 1, "pstddev")?;
+    Ok(pvariance(values)?.sqrt())
+}
+
+pub fn min(values: &[f64]) -> Result<f64, String> {
+    require(values, 1, "min")?;
+    Ok(values.iter().copied().fold(f64::INFINITY, f64::min))
+}
+
+pub fn max(values: &[f64]) -> Result<f64, String> {
+    require(values, 1, "max")?;
+    Ok(values.iter().copied().fold(f64::NEG_INFINITY, f64::max))
+}
+
+pub fn sum(values: &[f64]) -> Result<f64, String> {
+    Ok(values.iter().sum())
+}
+
+/// Least-squares line through the points: `(slope, intercept, r²)`.
+pub fn linreg(xs: &[f64], ys: &[f64]) -> Result<(f64, f64, f64), String> {
+    if xs.len() != ys.len() {
+        return Err(format!(
+            "linreg() needs lists of equal length, got {} and {}",
+            xs.len(),
+            ys.len()
+        ));
+    }
+    require(xs, 2, "linreg")?;
+
+    let mean_x = mean(xs)?;
+    let mean_y = mean(ys)?;
+    let sxx: f64 = xs.iter().map(|x| (x - mean_x).powi(2)).sum();
+    let sxy: f64 = xs
+        .iter()
+        .zip(ys)
+        .map(|(x, y)| (x - mean_x) * (y - mean_y))
+        .sum();
+    if sxx == 0.0 {
+        return Err("linreg() needs at least two different x values".to_string());
+    }
+
+    let slope = sxy / sxx;
+    let intercept = mean_y - slope * mean_x;
+    let total = squared_deviations(ys);
+    let residual: f64 = xs
+        .iter()
+        .zip(ys)
+        .map(|(x, y)| (y - (slope * x + intercept)).powi(2))
+        .sum();
+    let r2 = if total == 0.0 {
+        1.0
+    } else {
+        1.0 - residual / total
+    };
+    Ok((slope, intercept, r2))
+}
+
+/// Counts of the values in `bins` equal-width bins from the smallest value
+/// to the largest, with the largest value in the last bin.
+pub fn histogram(values: &[f64], bins: usize) -> Vec<usize> {
+    let mut counts = vec![0; bins];
+    let low = values.iter().copied().fold(f64::INFINITY, f64::min);
+    let high = values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    for &v in values {
+        let bin = if high > low {
+            ((v - low) / (high - low) * bins as f64) as usize
+        } else {
+            0
+        };
+        counts[bin.min(bins - 1)] += 1;
+    }
+    counts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DATA: [f64; 4] = [3.0, 7.0, 8.0, 12.0];
+
+    #[test]
+    fn test_mean_and_median() {
+        assert_eq!(mean(&DATA).unwrap(), 7.5);
+        assert_eq!(median(&DATA).unwrap(), 7.5);
+        assert_eq!(median(&[5.0, 1.0, 3.0]).unwrap(), 3.0);
+        assert!(mean(&[]).is_err());
+    }
+
+    #[test]
+    fn test_spread() {
+        assert_eq!(pvariance(&DATA).unwrap(), 10.25);
+        assert!((variance(&DATA).unwrap() - 13.666_666_666_666_666).abs() < 1e-12);
+        assert!((stddev(&DATA).unwrap() - 3.696_845_502_136_472).abs() < 1e-12);
+        assert!((pstddev(&DATA).unwrap() - 3.201_562_118_716_424).abs() < 1e-12);
+        assert!(variance(&[1.0]).is_err());
+    }
+
+    #[test]
+    fn test_linreg() {
+        let (slope, intercept, r2) = linreg(&[1.0, 2.0, 3.0], &[3.0, 5.0, 7.0]).unwrap();
+        assert_eq!((slope, intercept, r2), (2.0, 1.0, 1.0));
+
+        let (slope, _, r2) = linreg(&[1.0, 2.0, 3.0, 4.0], &[2.0, 4.0, 5.0, 4.0]).unwrap();
+        assert!((slope - 0.7).abs() < 1e-12);
+        assert!((r2 - 0.515_789_473_684_210_5).abs() < 1e-12);
+
+        assert!(linreg(&[1.0, 2.0], &[1.0]).is_err());
+        assert!(linreg(&[1.0, 1.0], &[1.0, 2.0]).is_err());
+    }
+
+    #[test]
+    fn test_min_max_sum() {
+        assert_eq!(min(&DATA).unwrap(), 3.0);
+        assert_eq!(max(&DATA).unwrap(), 12.0);
+        assert_eq!(sum(&DATA).unwrap(), 30.0);
+        assert_eq!(sum(&[]).unwrap(), 0.0);
+    }
+
+    #[test]
+    fn test_histogram() {
+        assert_eq!(histogram(&DATA, 3), [1, 2, 1]);
+        assert_eq!(histogram(&[0.0, 0.5, 1.0, 1.0], 2), [1, 3]);
+        assert_eq!(histogram(&[4.0, 4.0], 5), [2, 0, 0, 0, 0]);
+    }
+}
@@ -0,0 +1,199 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Current Unix time in seconds.
+pub fn now() -> f64 {
+    match SystemTime::now().duration_since(UNIX_EPOCH) {
+        Ok(elapsed) => elapsed.as_secs_f64(),
+        Err(e) => -e.duration().as_secs_f64(),
+    }
+}
+
+// Day count since 1970-01-01 for a proleptic Gregorian date
+// (Howard Hinnant's `days_from_civil`).
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let mp = (month + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+fn count_digits(chars: &[char], start: usize) -> usize {
+    chars[start.min(chars.len())..]
+        .iter()
+        .take_while(|c| c.is_ascii_digit())
+        .count()
+}
+
+fn scan_time_of_day(chars: &[char], start: usize) -> Option<usize> {
+    let hours = count_digits(chars, start);
+    if !(1..=2).contains(&hours) || chars.get(start + hours) != Some(&':') {
+        return None;
+    }
+    let mut i = start + hours + 1;
+    if count_digits(chars, i) != 2 {
+        return None;
+    }
+    i += 2;
+    if chars.get(i) == Some(&':') && count_digits(chars, i + 1) == 2 {
+        i += 3;
+    }
+    Some(i)
+}
+
+/// Years a date literal can have.
+const LITERAL_YEARS: std::ops::RangeInclusive<u32> = 1900..=2999;
+
+/// Length in chars of a date literal (`2024-05-01`, `2024-05-01 12:00`,
+/// `2024-05-01T12:00:30`) at the start of `chars`, if any.
+pub fn scan_date(chars: &[char]) -> Option<usize> {
+    let end = scan_shape(chars)?;
+    // Anything else stays arithmetic: `1234-56-78` is not a real date, and
+    // `1000-10-20` is far more likely a subtraction than a date.
+    let year: u32 = chars[..4].iter().collect::<String>().parse().ok()?;
+    if !LITERAL_YEARS.contains(&year) {
+        return None;
+    }
+    let is_date = |end: usize| date_value(&chars[..end].iter().collect::<String>()).is_some();
+    if is_date(end) {
+        Some(end)
+    } else if end > 10 && is_date(10) {
+        Some(10)
+    } else {
+        None
+    }
+}
+
+/// Length of the text shaped like a date (`dddd-dd-dd`, optionally with a
+/// time of day), whether or not it is a valid one.
+fn scan_shape(chars: &[char]) -> Option<usize> {
+    let shape = [4, 2, 2];
+    let mut i = 0;
+    for (n, &len) in shape.iter().enumerate() {
+        if count_digits(chars, i) != len {
+            return None;
+        }
+        i += len;
+        if n < 2 {
+            if chars.get(i) != Some(&'-') {
+                return None;
+            }
+            i += 1;
+        }
+    }
+
+    if let Some(&sep) = chars.get(i)
+        && (sep == ' ' || sep == 'T')
+        && let Some(end) = scan_time_of_day(chars, i + 1)
+    {
+        return Some(end);
+    }
+    Some(i)
+}
+
+/// Parses a date literal (see [`scan_date`]) as UTC into Unix seconds.
+pub fn parse_date(text: &str) -> Option<f64> {
+    let text = text.strip_suffix(" UTC").unwrap_or(text);
+    let chars: Vec<char> = text.chars().collect();
+    if scan_shape(&chars)? != chars.len() {
+        return None;
+    }
+    date_value(text)
+}
+
+/// Unix seconds for text already shaped like a date, if it is a real one.
+fn date_value(text: &str) -> Option<f64> {
+    let numbers: Vec<i64> = text
+        .split(['-', ' ', 'T', ':'])
+        .map(|p| p.parse::<i64>().ok())
+        .collect::<Option<_>>()?;
+    let (year, month, day) = (numbers[0], numbers[1], numbers[2]);
+    let hour = numbers.get(3).copied().unwrap_or(0);
+    let minute = numbers.get(4).copied().unwrap_or(0);
+    let second = numbers.get(5).copied().unwrap_or(0);
+
+    if !(1..=12).contains(&month)
+        || !(1..=31).contains(&day)
+        || hour > 23
+        || minute > 59
+        || second > 59
+    {
+        return None;
+    }
+    if civil_from_days(days_from_civil(year, month, day)) != (year, month, day) {
+        return None;
+    }
+
+    let days = days_from_civil(year, month, day);
+    Some((days * 86_400 + hour * 3600 + minute * 60 + second) as f64)
+}
+
+/// Formats Unix seconds as `YYYY-MM-DD hh:mm:ss UTC`.
+pub fn format_timestamp(seconds: f64) -> String {
+    let whole = seconds.floor() as i64;
+    let (year, month, day) = civil_from_days(whole.div_euclid(86_400));
+    let secs = whole.rem_euclid(86_400);
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02} UTC",
+        year,
+        month,
+        day,
+        secs / 3600,
+        secs / 60 % 60,
+        secs % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scan(text: &str) -> Option<usize> {
+        scan_date(&text.chars().collect::<Vec<char>>())
+    }
+
+    #[test]
+    fn test_scan_date() {
+        assert_eq!(scan("2024-05-01"), Some(10));
+        assert_eq!(scan("2024-05-01 12:00)"), Some(16));
+        assert_eq!(scan("2024-05-01T12:00:30"), Some(19));
+        assert_eq!(scan("2024-05-01 + 1h"), Some(10));
+        assert_eq!(scan("2024-5-1"), None);
+        assert_eq!(scan("2024 - 05"), None);
+        assert_eq!(scan("1234-56-78"), None);
+        assert_eq!(scan("1000-10-20"), None);
+        assert_eq!(scan("2024-02-30"), None);
+        assert_eq!(scan("2024-05-01 12:00:75"), Some(10));
+    }
+
+    #[test]
+    fn test_parse_date() {
+        assert_eq!(parse_date("1970-01-01"), Some(0.0));
+        assert_eq!(parse_date("2024-05-01 10:00"), Some(1714557600.0));
+        assert_eq!(parse_date("2024-05-01 10:00:00 UTC"), Some(1714557600.0));
+        assert_eq!(parse_date("2023-02-29"), None);
+        assert_eq!(parse_date("2024-13-01"), None);
+        assert_eq!(parse_date("2024-05-01 12:00:60"), None);
+    }
+
+    #[test]
+    fn test_format_timestamp() {
+        assert_eq!(format_timestamp(1714557600.0), "2024-05-01 10:00:00 UTC");
+        assert_eq!(format_timestamp(-1.0), "1969-12-31 23:59:59 UTC");
+    }
+}
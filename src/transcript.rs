@@ -0,0 +1,62 @@
+//! A record of the whole session, written out with `transcript <path>`.
+
+use std::fs;
+
+#[derive(Debug, Default)]
+pub struct Transcript {
+    lines: Vec<String>,
+}
+
+impl Transcript {
+    pub fn input(&mut self, line: &str) {
+        self.lines.push(format!("> {}", line));
+    }
+
+    /// Records printed text, dropping its colours.
+    pub fn output(&mut self, text: &str) {
+        self.lines.push(strip_colours(text));
+    }
+
+    pub fn text(&self) -> String {
+        let mut text = self.lines.join("\n");
+        text.push('\n');
+        text
+    }
+
+    pub fn save(&self, path: &str) -> Result<String, String> {
+        fs::write(path, self.text()).map_err(|e| format!("Cannot write {}: {}", path, e))?;
+        Ok(format!("Session written to '{}'", path))
+    }
+}
+
+/// Removes ANSI colour codes such as `\x1b[31m`.
+fn strip_colours(text: &str) -> String {
+    let mut plain = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            chars.by_ref().find(|&c| c == 'm');
+        } else {
+            plain.push(c);
+        }
+    }
+    plain
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transcript_text() {
+        let mut transcript = Transcript::default();
+        transcript.input("1 / 0");
+        transcript.output("\x1b[31mError:\x1b[0m \x1b[31mDivision by zero\x1b[0m\n");
+        transcript.input("2 + 2");
+        transcript.output("= 4\n");
+        assert_eq!(
+            transcript.text(),
+            "> 1 / 0\nError: Division by zero\n\n> 2 + 2\n= 4\n\n"
+        );
+    }
+}
@@ -0,0 +1,256 @@
+use crate::bigint::BigUint;
+use crate::calculate;
+use crate::duration::{format_duration, parse_duration};
+use crate::matrix::Matrix;
+use crate::network::{format_address, parse_address, parse_network};
+use crate::settings::Settings;
+use crate::timestamp::{format_timestamp, parse_date};
+use crate::vector;
+use core::fmt;
+
+/// A calculation result. Durations are kept in seconds, points in time
+/// as Unix seconds (UTC).
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Number(f64),
+    /// A whole number worked out exactly but too large for a float without
+    /// rounding, such as `fib(100)`. Arithmetic uses it as a rounded number.
+    Big(BigUint),
+    Duration(f64),
+    Time(f64),
+    Text(String),
+    List(Vec<f64>),
+    Matrix(Matrix),
+    Address(u32),
+    /// An IPv4 network: its address and prefix length.
+    Network(u32, u8),
+}
+
+impl Value {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Value::Number(_) | Value::Big(_) => "number",
+            Value::Duration(_) => "duration",
+            Value::Time(_) => "date",
+            Value::Text(_) => "text",
+            Value::List(_) => "list",
+            Value::Matrix(_) => "matrix",
+            Value::Address(_) => "address",
+            Value::Network(..) => "network",
+        }
+    }
+
+    /// The value as shown in the REPL, with the decimals settings applied
+    /// to numbers. `Display` keeps the exact form for `history.txt`.
+    pub fn show(&self, settings: &Settings) -> String {
+        match self {
+            Value::Number(n) => settings.format_number(*n),
+            Value::List(items) => {
+                let items: Vec<String> = items.iter().map(|&n| settings.format_number(n)).collect();
+                format!("[{}]", items.join(", "))
+            }
+            other => other.to_string(),
+        }
+    }
+
+    /// Parses a value as written by `Display`, e.g. from `history.txt`.
+    /// Anything that is not a number, duration or date is kept as text.
+    pub fn parse(text: &str) -> Option<Value> {
+        if text.is_empty() {
+            return None;
+        }
+        if let Ok(n) = text.parse::<f64>() {
+            return Some(Value::Number(n));
+        }
+        if let Some(items) = text.strip_prefix('[').and_then(|t| t.strip_suffix(']')) {
+            if let Some(rows) = items.strip_prefix('[').and_then(|t| t.strip_suffix(']'))
+                && let Some(rows) = rows
+                    .split("], [")
+                    .map(parse_numbers)
+                    .collect::<Option<Vec<_>>>()
+                && let Ok(matrix) = Matrix::from_rows(rows)
+            {
+                return Some(Value::Matrix(matrix));
+            }
+            if let Some(list) = parse_numbers(items) {
+                return Some(Value::List(list));
+            }
+        }
+        if let Some(address) = parse_address(text) {
+            return Some(Value::Address(address));
+        }
+        if let Some((address, prefix)) = parse_network(text) {
+            return Some(Value::Network(address, prefix));
+        }
+        let value = parse_duration(text)
+            .map(Value::Duration)
+            .or_else(|| parse_date(text).map(Value::Time))
+            .unwrap_or_else(|| Value::Text(text.to_string()));
+        Some(value)
+    }
+
+    /// A `Big` as an ordinary number; anything else as it is.
+    fn rounded(self) -> Value {
+        match self {
+            Value::Big(n) => Value::Number(n.to_f64()),
+            other => other,
+        }
+    }
+}
+
+fn parse_numbers(items: &str) -> Option<Vec<f64>> {
+    items
+        .split(',')
+        .filter(|item| !item.trim().is_empty())
+        .map(|item| item.trim().parse::<f64>().ok())
+        .collect()
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Value::Number(n) => write!(f, "{}", n),
+            Value::Big(n) => write!(f, "{}", n),
+            Value::Duration(secs) => write!(f, "{}", format_duration(*secs)),
+            Value::Time(secs) => write!(f, "{}", format_timestamp(*secs)),
+            Value::Text(text) => write!(f, "{}", text),
+            Value::List(items) => {
+                let items: Vec<String> = items.iter().map(|n| n.to_string()).collect();
+                write!(f, "[{}]", items.join(", "))
+            }
+            Value::Matrix(matrix) => write!(f, "{}", matrix),
+            Value::Address(address) => write!(f, "{}", format_address(*address)),
+            Value::Network(0, prefix) => write!(f, "/{}", prefix),
+            Value::Network(address, prefix) => {
+                write!(f, "{}/{}", format_address(*address), prefix)
+            }
+        }
+    }
+}
+
+/// Applies a unary operator: `-` (negation), `s` (square root) or `%`
+/// (percent).
+pub fn apply_unary(op: char, a: Value) -> Result<Value, String> {
+    match op {
+        '-' => apply_values(a, '*', Value::Number(-1.0)),
+        '%' => apply_values(a, '/', Value::Number(100.0)),
+        _ => apply_values(a, op, Value::Number(0.0)),
+    }
+}
+
+/// Applies a binary operator to two values, checking that the kinds fit.
+pub fn apply_values(a: Value, op: char, b: Value) -> Result<Value, String> {
+    use Value::{Duration, List, Matrix, Number, Time};
+
+    match (a.rounded(), op, b.rounded()) {
+        (Number(a), _, Number(b)) => calculate(a, op, b).map(Number),
+        (Duration(a), '+' | '-' | '%', Duration(b)) => calculate(a, op, b).map(Duration),
+        (Duration(a), '/', Duration(b)) => calculate(a, op, b).map(Number),
+        (Duration(a), '*' | '/', Number(b)) => calculate(a, op, b).map(Duration),
+        (Number(a), '*', Duration(b)) => calculate(a, op, b).map(Duration),
+        (Time(a), '+' | '-', Duration(b)) => calculate(a, op, b).map(Time),
+        (Duration(a), '+', Time(b)) => calculate(a, op, b).map(Time),
+        (Time(a), '-', Time(b)) => calculate(a, op, b).map(Duration),
+        (List(a), '+' | '-', List(b)) => vector::combine(&a, op, &b).map(List),
+        (List(a), _, Number(b)) => vector::broadcast(&a, op, b, false).map(List),
+        (Number(a), _, List(b)) => vector::broadcast(&b, op, a, true).map(List),
+        (Matrix(a), '+' | '-', Matrix(b)) => a.combine(&b, op).map(Matrix),
+        (Matrix(a), '*', Matrix(b)) => a.mul(&b).map(Matrix),
+        (Matrix(a), '*', Number(b)) | (Number(b), '*', Matrix(a)) => Ok(Matrix(a.map(|x| x * b))),
+        (Matrix(a), '/', Number(b)) => {
+            calculate(1.0, '/', b).map(|inverse| Matrix(a.map(|x| x * inverse)))
+        }
+        (a, op, b) => Err(format!(
+            "Error: Cannot apply '{}' to {} and {}",
+            op,
+            a.kind(),
+            b.kind()
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_duration_arithmetic() {
+        let sum = apply_values(Value::Duration(5400.0), '+', Value::Duration(2700.0));
+        assert_eq!(sum.unwrap(), Value::Duration(8100.0));
+
+        let quarter = apply_values(Value::Duration(5400.0), '/', Value::Number(4.0));
+        assert_eq!(quarter.unwrap(), Value::Duration(1350.0));
+
+        let ratio = apply_values(Value::Duration(5400.0), '/', Value::Duration(1800.0));
+        assert_eq!(ratio.unwrap(), Value::Number(3.0));
+    }
+
+    #[test]
+    fn test_big_values_in_arithmetic() {
+        let big = Value::Big(BigUint::from_u64(u64::MAX));
+        assert_eq!(big.to_string(), "18446744073709551615");
+        let sum = apply_values(big, '+', Value::Number(1.0)).unwrap();
+        assert_eq!(sum, Value::Number(18446744073709551616.0));
+    }
+
+    #[test]
+    fn test_mixed_kinds_rejected() {
+        assert!(apply_values(Value::Duration(60.0), '+', Value::Number(1.0)).is_err());
+        assert!(apply_values(Value::Number(2.0), '^', Value::Duration(60.0)).is_err());
+        assert!(apply_values(Value::Time(0.0), '+', Value::Time(60.0)).is_err());
+    }
+
+    #[test]
+    fn test_matrix_arithmetic() {
+        let a = Value::parse("[[1, 2], [3, 4]]").unwrap();
+        let b = Value::parse("[[5], [6]]").unwrap();
+        let product = apply_values(a.clone(), '*', b.clone()).unwrap();
+        assert_eq!(product.to_string(), "[[17], [39]]");
+        let doubled = apply_values(Value::Number(2.0), '*', a.clone()).unwrap();
+        assert_eq!(doubled.to_string(), "[[2, 4], [6, 8]]");
+        assert!(apply_values(a.clone(), '+', b).is_err());
+        assert!(apply_values(a, '/', Value::Number(0.0)).is_err());
+    }
+
+    #[test]
+    fn test_parse_network_values() {
+        assert_eq!(Value::parse("10.0.0.1"), Some(Value::Address(0x0a00_0001)));
+        let network = Value::parse("192.168.1.0/24").unwrap();
+        assert_eq!(network.to_string(), "192.168.1.0/24");
+        assert_eq!(Value::parse("/26"), Some(Value::Network(0, 26)));
+        assert_eq!(Value::Network(0, 26).to_string(), "/26");
+    }
+
+    #[test]
+    fn test_vector_arithmetic() {
+        let a = Value::List(vec![1.0, 2.0, 3.0]);
+        let sum = apply_values(a.clone(), '+', Value::List(vec![1.0, 1.0, 1.0]));
+        assert_eq!(sum.unwrap(), Value::List(vec![2.0, 3.0, 4.0]));
+        let scaled = apply_values(Value::Number(2.0), '*', a.clone());
+        assert_eq!(scaled.unwrap(), Value::List(vec![2.0, 4.0, 6.0]));
+        let halved = apply_values(a.clone(), '/', Value::Number(2.0));
+        assert_eq!(halved.unwrap(), Value::List(vec![0.5, 1.0, 1.5]));
+        assert!(apply_values(a.clone(), '*', a).is_err());
+    }
+
+    #[test]
+    fn test_time_arithmetic() {
+        let later = apply_values(Value::Time(0.0), '+', Value::Duration(90.0));
+        assert_eq!(later.unwrap(), Value::Time(90.0));
+
+        let gap = apply_values(Value::Time(7200.0), '-', Value::Time(1800.0));
+        assert_eq!(gap.unwrap(), Value::Duration(5400.0));
+    }
+
+    #[test]
+    fn test_value_display_and_parse() {
+        assert_eq!(Value::Duration(5400.0).to_string(), "1:30:00");
+        assert_eq!(Value::parse("1:30:00"), Some(Value::Duration(5400.0)));
+        assert_eq!(Value::parse("8"), Some(Value::Number(8.0)));
+        assert_eq!(Value::parse("MMXXIV"), Some(Value::Text("MMXXIV".into())));
+        assert_eq!(Value::parse(""), None);
+        assert_eq!(Value::List(vec![1.0, 2.5]).to_string(), "[1, 2.5]");
+        assert_eq!(Value::parse("[1, 2.5]"), Some(Value::List(vec![1.0, 2.5])));
+        assert_eq!(Value::parse("[]"), Some(Value::List(vec![])));
+    }
+}
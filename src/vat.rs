@@ -0,0 +1,50 @@
+/// VAT rates are fractions, as for `tip` and `amortize`: written `21%` (or
+/// `0.21`) in an expression. A bare `21` is out of range rather than 2100%.
+pub fn check_rate(rate: f64) -> Result<f64, String> {
+    if !(0.0..=1.0).contains(&rate) {
+        return Err(format!(
+            "VAT rate {} is out of range, write it as a percentage such as 21%",
+            rate
+        ));
+    }
+    Ok(rate)
+}
+
+/// A rate as a percentage for display, without float noise (`7%`, not
+/// `7.000000000000001%`).
+pub fn format_rate(rate: f64) -> String {
+    format!("{}%", (rate * 100.0 * 1e10).round() / 1e10)
+}
+
+/// Gross amount for a net amount plus VAT at `rate`.
+pub fn add_vat(net: f64, rate: f64) -> f64 {
+    net + net * rate
+}
+
+/// Net amount contained in a gross amount including VAT at `rate`.
+pub fn remove_vat(gross: f64, rate: f64) -> f64 {
+    gross * 100.0 / (100.0 + rate * 100.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_add_and_remove_vat() {
+        assert!((add_vat(100.0, 0.21) - 121.0).abs() < 1e-9);
+        assert!((remove_vat(121.0, 0.21) - 100.0).abs() < 1e-9);
+        assert_eq!(remove_vat(110.0, 0.1), 100.0);
+        assert_eq!(add_vat(50.0, 0.0), 50.0);
+    }
+
+    #[test]
+    fn test_check_rate() {
+        assert_eq!(check_rate(0.21).unwrap(), 0.21);
+        assert_eq!(check_rate(1.0).unwrap(), 1.0);
+        assert!(check_rate(21.0).is_err());
+        assert!(check_rate(-0.05).is_err());
+        assert_eq!(format_rate(0.07), "7%");
+        assert_eq!(format_rate(0.005), "0.5%");
+    }
+}
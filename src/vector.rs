@@ -0,0 +1,92 @@
+use crate::calculate;
+
+fn require_same_length(name: &str, a: &[f64], b: &[f64]) -> Result<(), String> {
+    if a.len() == b.len() {
+        Ok(())
+    } else {
+        Err(format!(
+            "{} needs vectors of equal length, got {} and {}",
+            name,
+            a.len(),
+            b.len()
+        ))
+    }
+}
+
+pub fn dot(a: &[f64], b: &[f64]) -> Result<f64, String> {
+    require_same_length("dot()", a, b)?;
+    Ok(a.iter().zip(b).map(|(x, y)| x * y).sum())
+}
+
+pub fn cross(a: &[f64], b: &[f64]) -> Result<Vec<f64>, String> {
+    if a.len() != 3 || b.len() != 3 {
+        return Err("cross() needs two vectors with 3 components".to_string());
+    }
+    Ok(vec![
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ])
+}
+
+/// Euclidean length.
+pub fn norm(a: &[f64]) -> f64 {
+    a.iter().map(|x| x * x).sum::<f64>().sqrt()
+}
+
+/// Element-wise `a op b`; only `+` and `-` make sense between two vectors.
+pub fn combine(a: &[f64], op: char, b: &[f64]) -> Result<Vec<f64>, String> {
+    require_same_length(&format!("'{}'", op), a, b)?;
+    a.iter()
+        .zip(b)
+        .map(|(x, y)| calculate(*x, op, *y))
+        .collect()
+}
+
+/// Applies `op` between every element and a single number. With
+/// `number_first` the number is the left operand (`2 * [1, 2]`).
+pub fn broadcast(items: &[f64], op: char, n: f64, number_first: bool) -> Result<Vec<f64>, String> {
+    items
+        .iter()
+        .map(|&x| {
+            if number_first {
+                calculate(n, op, x)
+            } else {
+                calculate(x, op, n)
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dot_cross_norm() {
+        assert_eq!(dot(&[1.0, 2.0, 3.0], &[4.0, 5.0, 6.0]).unwrap(), 32.0);
+        assert!(dot(&[1.0], &[1.0, 2.0]).is_err());
+        let z = cross(&[1.0, 0.0, 0.0], &[0.0, 1.0, 0.0]).unwrap();
+        assert_eq!(z, vec![0.0, 0.0, 1.0]);
+        assert!(cross(&[1.0, 2.0], &[3.0, 4.0]).is_err());
+        assert_eq!(norm(&[3.0, 4.0]), 5.0);
+    }
+
+    #[test]
+    fn test_broadcast() {
+        assert_eq!(
+            broadcast(&[1.0, 2.0], '*', 3.0, false).unwrap(),
+            vec![3.0, 6.0]
+        );
+        assert_eq!(
+            broadcast(&[1.0, 2.0], '-', 10.0, true).unwrap(),
+            vec![9.0, 8.0]
+        );
+        assert!(broadcast(&[1.0, 2.0], '/', 0.0, false).is_err());
+        assert_eq!(
+            combine(&[1.0, 2.0], '+', &[3.0, 4.0]).unwrap(),
+            vec![4.0, 6.0]
+        );
+        assert!(combine(&[1.0, 2.0], '+', &[3.0]).is_err());
+    }
+}
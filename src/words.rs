@@ -0,0 +1,158 @@
+const ONES: [&str; 20] = [
+    "zero",
+    "one",
+    "two",
+    "three",
+    "four",
+    "five",
+    "six",
+    "seven",
+    "eight",
+    "nine",
+    "ten",
+    "eleven",
+    "twelve",
+    "thirteen",
+    "fourteen",
+    "fifteen",
+    "sixteen",
+    "seventeen",
+    "eighteen",
+    "nineteen",
+];
+
+const TENS: [&str; 10] = [
+    "", "", "twenty", "thirty", "forty", "fifty", "sixty", "seventy", "eighty", "ninety",
+];
+
+const SCALES: [(u64, &str); 4] = [
+    (1_000_000_000_000, "trillion"),
+    (1_000_000_000, "billion"),
+    (1_000_000, "million"),
+    (1_000, "thousand"),
+];
+
+const LIMIT: f64 = 1e15;
+
+fn below_thousand(n: u64) -> String {
+    let mut parts = Vec::new();
+    if n >= 100 {
+        parts.push(format!("{} hundred", ONES[(n / 100) as usize]));
+    }
+    let rest = n % 100;
+    if rest >= 20 {
+        let tens = TENS[(rest / 10) as usize];
+        if rest.is_multiple_of(10) {
+            parts.push(tens.to_string());
+        } else {
+            parts.push(format!("{}-{}", tens, ONES[(rest % 10) as usize]));
+        }
+    } else if rest > 0 || parts.is_empty() {
+        parts.push(ONES[rest as usize].to_string());
+    }
+    parts.join(" ")
+}
+
+fn integer_to_words(mut n: u64) -> String {
+    if n == 0 {
+        return ONES[0].to_string();
+    }
+    let mut parts = Vec::new();
+    for &(scale, name) in SCALES.iter() {
+        if n >= scale {
+            parts.push(format!("{} {}", below_thousand(n / scale), name));
+            n %= scale;
+        }
+    }
+    if n > 0 {
+        parts.push(below_thousand(n));
+    }
+    parts.join(" ")
+}
+
+fn check_range(n: f64) -> Result<(), String> {
+    if !n.is_finite() || n.abs() >= LIMIT {
+        Err(format!("Cannot spell out {}", n))
+    } else {
+        Ok(())
+    }
+}
+
+/// Spells out a number, reading decimals digit by digit:
+/// `1234.56` → "one thousand two hundred thirty-four point five six".
+pub fn number_to_words(n: f64) -> Result<String, String> {
+    check_range(n)?;
+    let text = n.abs().to_string();
+    let (whole, fraction) = text.split_once('.').unwrap_or((&text, ""));
+
+    let mut out = String::new();
+    if n < 0.0 {
+        out.push_str("minus ");
+    }
+    out.push_str(&integer_to_words(whole.parse::<u64>().unwrap_or(0)));
+    if !fraction.is_empty() {
+        out.push_str(" point");
+        for digit in fraction.chars().filter_map(|c| c.to_digit(10)) {
+            out.push(' ');
+            out.push_str(ONES[digit as usize]);
+        }
+    }
+    Ok(out)
+}
+
+/// Spells out an amount the way it is written on a cheque:
+/// `1234.56` → "one thousand two hundred thirty-four dollars and fifty-six cents".
+pub fn money_to_words(amount: f64) -> Result<String, String> {
+    check_range(amount)?;
+    let cents_total = (amount.abs() * 100.0).round() as u64;
+    let (dollars, cents) = (cents_total / 100, cents_total % 100);
+
+    let plural = |n: u64, one: &str, many: &str| {
+        format!(
+            "{} {}",
+            integer_to_words(n),
+            if n == 1 { one } else { many }
+        )
+    };
+
+    let sign = if amount < 0.0 { "minus " } else { "" };
+    Ok(format!(
+        "{}{} and {}",
+        sign,
+        plural(dollars, "dollar", "dollars"),
+        plural(cents, "cent", "cents")
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_number_to_words() {
+        assert_eq!(
+            number_to_words(1234.56).unwrap(),
+            "one thousand two hundred thirty-four point five six"
+        );
+        assert_eq!(number_to_words(0.0).unwrap(), "zero");
+        assert_eq!(number_to_words(-15.0).unwrap(), "minus fifteen");
+        assert_eq!(
+            number_to_words(2_000_017.0).unwrap(),
+            "two million seventeen"
+        );
+        assert!(number_to_words(1e16).is_err());
+    }
+
+    #[test]
+    fn test_money_to_words() {
+        assert_eq!(
+            money_to_words(1234.56).unwrap(),
+            "one thousand two hundred thirty-four dollars and fifty-six cents"
+        );
+        assert_eq!(money_to_words(1.01).unwrap(), "one dollar and one cent");
+        assert_eq!(
+            money_to_words(40.0).unwrap(),
+            "forty dollars and zero cents"
+        );
+    }
+}